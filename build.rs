@@ -0,0 +1,9 @@
+// sets the `gamepad` cfg the gamepad code is built with: always on Windows and macOS, where gilrs needs
+// nothing else, and on other platforms only with the `gamepad` feature, as gilrs needs libudev on Linux
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(gamepad)");
+    let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    if target_os == "windows" || target_os == "macos" || std::env::var_os("CARGO_FEATURE_GAMEPAD").is_some() {
+        println!("cargo::rustc-cfg=gamepad");
+    }
+}
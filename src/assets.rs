@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use macroquad::prelude::*;
+use macroquad_tiled::{load_map, Map};
+use macroUtils::include_texture;
+use nanoserde::DeJson;
+use crate::stage::Stage;
+use crate::tiled::{macroquad_tiled_json, TiledMap};
+
+/// directory the maps and tilesets are read from in asset path mode. Paths in `levels.json` and
+/// the tileset paths in the maps are relative to it
+pub const ASSETS_DIR: &str = "assets";
+
+/// whether maps are read from `ASSETS_DIR` and reloaded when they change: always in debug builds,
+/// in release builds only with `--assets`. Otherwise the embedded maps are used
+pub fn live_assets() -> bool {
+    cfg!(debug_assertions) || std::env::args().any(|arg| arg == "--assets")
+}
+
+/// path of an asset in `ASSETS_DIR`
+pub fn asset_path(path: &str) -> PathBuf {
+    Path::new(ASSETS_DIR).join(path)
+}
+
+/// builds the tilemaps of the stages and, in asset path mode, watches their files for changes
+pub struct MapLoader {
+    pub live: bool,
+    /// tileset textures by their path in the map
+    tilesets: HashMap<String, Texture2D>,
+    /// files of the current map and their last modification time
+    watched: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+impl MapLoader {
+    pub fn new(live: bool) -> Self {
+        Self {
+            live,
+            tilesets: HashMap::new(),
+            watched: HashMap::new(),
+        }
+    }
+
+    /// the texture of a tileset. Read from `ASSETS_DIR` in asset path mode, falling back to the embedded tilemap
+    fn tileset(&mut self, image: &str) -> Texture2D {
+        if let Some(texture) = self.tilesets.get(image) {
+            return texture.clone();
+        }
+        let from_disk = if self.live {
+            match std::fs::read(asset_path(image)) {
+                Ok(bytes) => Some(Texture2D::from_file_with_format(&bytes, None)),
+                Err(err) => {
+                    println!("WARN: couldn't load tileset {}: {}", image, err);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let texture = from_disk.unwrap_or_else(|| include_texture!("../assets/tilemap.png"));
+        self.tilesets.insert(image.to_string(), texture.clone());
+        texture
+    }
+
+    /// build the tilemap of `stage` and start watching its files. `None` if the map is broken
+    pub fn load(&mut self, stage: &Stage) -> Option<Map> {
+        let tiled = match TiledMap::deserialize_json(&stage.map) {
+            Ok(tiled) => tiled,
+            Err(err) => {
+                println!("WARN: couldn't parse map {}: {}", stage.map_path, err);
+                return None;
+            }
+        };
+        let problems = tiled.problems();
+        if !problems.is_empty() {
+            println!("WARN: can't use map {}: {}", stage.map_path, problems.join(", "));
+            return None;
+        }
+        let textures: Vec<(String, Texture2D)> = tiled.tilesets.iter().map(|tileset| (tileset.image.clone(), self.tileset(&tileset.image))).collect();
+        let textures: Vec<(&str, Texture2D)> = textures.iter().map(|(image, texture)| (image.as_str(), texture.clone())).collect();
+        let map = match load_map(&macroquad_tiled_json(&stage.map), &textures, &[]) {
+            Ok(map) => map,
+            Err(err) => {
+                println!("WARN: couldn't load map {}: {}", stage.map_path, err);
+                return None;
+            }
+        };
+
+        if self.live {
+            self.watched.clear();
+            for path in std::iter::once(&stage.map_path).chain(tiled.tilesets.iter().map(|tileset| &tileset.image)) {
+                let path = asset_path(path);
+                let modified = modified(&path);
+                self.watched.insert(path, modified);
+            }
+        }
+        Some(map)
+    }
+
+    /// whether one of the files of the current map was saved since the last check. Forgets the
+    /// cached tilesets if so, so the reload picks up the new ones
+    pub fn changed(&mut self) -> bool {
+        if !self.live {
+            return false;
+        }
+        let mut changed = false;
+        for (path, last_modified) in &mut self.watched {
+            let modified = modified(path);
+            if modified != *last_modified {
+                *last_modified = modified;
+                changed = true;
+            }
+        }
+        if changed {
+            self.tilesets.clear();
+        }
+        changed
+    }
+
+    /// re-read the map file of `stage` from `ASSETS_DIR` and build its tilemap again.
+    /// `None` (and the stage is left alone) if the file can't be read or is broken, e.g. while it's still being saved
+    pub fn reload(&mut self, stage: &mut Stage) -> Option<Map> {
+        let json = match std::fs::read_to_string(asset_path(&stage.map_path)) {
+            Ok(json) => json,
+            Err(err) => {
+                println!("WARN: couldn't reload map {}: {}", stage.map_path, err);
+                return None;
+            }
+        };
+        let old = std::mem::replace(&mut stage.map, json);
+        let map = self.load(stage);
+        if map.is_none() {
+            stage.map = old;
+        }
+        map
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use macroquad::audio::{load_sound_from_bytes, play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound};
+use crate::assets::asset_path;
+use crate::settings::Settings;
+use crate::stage::Stage;
+use crate::world::WorldEvent;
+
+/// copies of the same sample that may play at once. Further ones are dropped, so a room full of
+/// dying enemies doesn't turn into noise
+const MAX_INSTANCES: usize = 3;
+/// seconds a sample counts as playing if its length can't be read from the file
+const DEFAULT_LENGTH: f64 = 0.5;
+
+/// how much quieter the music gets while the game is paused
+const DUCK_VOLUME: f32 = 0.3;
+/// seconds the music takes to change between the menus and a run
+pub const MUSIC_FADE: f32 = 1.;
+
+/// music tracks of the scenes outside a run, relative to `ASSETS_DIR`
+pub const MENU_MUSIC: &str = "music/menu.wav";
+pub const DEATH_MUSIC: &str = "music/death.wav";
+pub const VICTORY_MUSIC: &str = "music/victory.wav";
+/// music of the stages that don't name their own, like the endless mode's rooms
+pub const STAGE_MUSIC: &str = "music/stage.wav";
+
+/// the samples that ship with the game
+const SAMPLES: [(&str, &[u8]); 2] = [
+    ("sfx::shoot", include_bytes!("../assets/shoot.wav")),
+    ("sfx::powerup", include_bytes!("../assets/powerup.wav")),
+];
+
+/// whether sound is played: only if the game was built with the `audio` feature and not started with `--mute`
+pub fn sound_enabled() -> bool {
+    cfg!(feature = "audio") && !std::env::args().any(|arg| arg == "--mute")
+}
+
+/// the sound cues of the gameplay
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Sfx {
+    Shot,
+    Hit,
+    EnemyDeath,
+    Pickup,
+    PlayerHurt,
+    StageClear,
+}
+
+impl Sfx {
+    /// the cue of a world event, if it has one
+    pub fn from_event(event: &WorldEvent) -> Option<Self> {
+        match event {
+            WorldEvent::PlayerShot => Some(Sfx::Shot),
+            WorldEvent::EnemyHit(_) | WorldEvent::BossHit => Some(Sfx::Hit),
+            WorldEvent::EnemyKilled | WorldEvent::BossDefeated => Some(Sfx::EnemyDeath),
+            WorldEvent::ItemPickedUp => Some(Sfx::Pickup),
+            WorldEvent::PlayerHurt => Some(Sfx::PlayerHurt),
+            WorldEvent::StageCleared(_) | WorldEvent::Won => Some(Sfx::StageClear),
+            _ => None,
+        }
+    }
+
+    /// the sample the cue plays and how loud, relative to the effect volume.
+    /// There are only two samples, so the cues share them
+    fn sample(self) -> (&'static str, f32) {
+        match self {
+            Sfx::Shot => ("sfx::shoot", 0.5),
+            Sfx::Hit => ("sfx::shoot", 0.3),
+            Sfx::EnemyDeath => ("sfx::shoot", 0.8),
+            Sfx::Pickup => ("sfx::powerup", 0.8),
+            Sfx::PlayerHurt => ("sfx::shoot", 1.),
+            Sfx::StageClear => ("sfx::powerup", 1.),
+        }
+    }
+}
+
+/// every music track the game may play: the ones of the scenes and the `music` of the stages
+pub fn music_tracks(stages: &[Stage]) -> Vec<String> {
+    let mut tracks: Vec<String> = [MENU_MUSIC, STAGE_MUSIC, DEATH_MUSIC, VICTORY_MUSIC].map(String::from).to_vec();
+    for stage in stages {
+        if !stage.music.is_empty() && !tracks.contains(&stage.music) {
+            tracks.push(stage.music.clone());
+        }
+    }
+    tracks
+}
+
+/// plays the loaded samples and music tracks, by name
+pub trait AudioBackend {
+    /// start the sound called `name` once at `volume` (0 to 1)
+    fn play(&mut self, name: &str, volume: f32);
+    /// start the sound called `name` over and over until it's stopped
+    fn play_looped(&mut self, name: &str, volume: f32);
+    /// change the volume of the sound called `name` while it's playing
+    fn set_volume(&mut self, name: &str, volume: f32);
+    fn stop(&mut self, name: &str);
+}
+
+/// plays through macroquad's audio
+pub struct MacroquadBackend {
+    sounds: HashMap<String, Sound>,
+}
+
+impl MacroquadBackend {
+    /// load the samples and the music `tracks` from `ASSETS_DIR`. Tracks that don't exist are left out,
+    /// the scenes that play them stay quiet
+    pub async fn load(tracks: &[String]) -> Self {
+        let mut sounds = HashMap::new();
+        for (name, bytes) in SAMPLES {
+            match load_sound_from_bytes(bytes).await {
+                Ok(sound) => {
+                    sounds.insert(name.to_string(), sound);
+                }
+                Err(err) => println!("WARN: couldn't load sound {}: {}", name, err),
+            }
+        }
+        for track in tracks {
+            let bytes = match std::fs::read(asset_path(track)) {
+                Ok(bytes) => bytes,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => {
+                    println!("WARN: couldn't load music {}: {}", track, err);
+                    continue;
+                }
+            };
+            match load_sound_from_bytes(&bytes).await {
+                Ok(sound) => {
+                    sounds.insert(track.clone(), sound);
+                }
+                Err(err) => println!("WARN: couldn't load music {}: {}", track, err),
+            }
+        }
+        Self { sounds }
+    }
+}
+
+impl AudioBackend for MacroquadBackend {
+    fn play(&mut self, name: &str, volume: f32) {
+        if let Some(sound) = self.sounds.get(name) {
+            play_sound(sound, PlaySoundParams { looped: false, volume });
+        }
+    }
+    fn play_looped(&mut self, name: &str, volume: f32) {
+        if let Some(sound) = self.sounds.get(name) {
+            play_sound(sound, PlaySoundParams { looped: true, volume });
+        }
+    }
+    fn set_volume(&mut self, name: &str, volume: f32) {
+        if let Some(sound) = self.sounds.get(name) {
+            set_sound_volume(sound, volume);
+        }
+    }
+    fn stop(&mut self, name: &str) {
+        if let Some(sound) = self.sounds.get(name) {
+            stop_sound(sound);
+        }
+    }
+}
+
+/// plays nothing, for headless runs and builds without sound
+pub struct NullBackend;
+
+impl AudioBackend for NullBackend {
+    fn play(&mut self, _name: &str, _volume: f32) {}
+    fn play_looped(&mut self, _name: &str, _volume: f32) {}
+    fn set_volume(&mut self, _name: &str, _volume: f32) {}
+    fn stop(&mut self, _name: &str) {}
+}
+
+/// length of a PCM wav file in seconds, from its `fmt ` and `data` chunks
+fn wav_length(bytes: &[u8]) -> Option<f64> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return None;
+    }
+    let mut byte_rate = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().ok()?) as usize;
+        let data = offset + 8;
+        if id == b"fmt " && data + 12 <= bytes.len() {
+            byte_rate = Some(u32::from_le_bytes(bytes[data + 8..data + 12].try_into().ok()?));
+        } else if id == b"data" {
+            return byte_rate.filter(|rate| *rate > 0).map(|rate| size as f64 / rate as f64);
+        }
+        // chunks are padded to an even size
+        offset = data + size + size % 2;
+    }
+    None
+}
+
+/// a music track that's playing, maybe fading in or out
+struct Channel {
+    track: String,
+    /// 0 to 1, multiplied with the music volume
+    level: f32,
+    /// the level the track fades to. Stopped once it's faded out
+    target: f32,
+    /// change of `level` per second
+    fade_speed: f32,
+    /// the volume last handed to the backend
+    volume: f32,
+}
+
+/// plays the sound cues, at most `MAX_INSTANCES` of a sample at once, and the music
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    /// length of every sample in seconds
+    lengths: HashMap<&'static str, f64>,
+    /// when the playing instances of every sample end
+    playing: HashMap<&'static str, Vec<f64>>,
+    /// the music tracks that are playing. The last one is the current track, the others fade out
+    music: Vec<Channel>,
+    /// whether the music is turned down, e.g. while the game is paused
+    ducked: bool,
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn AudioBackend>) -> Self {
+        Self {
+            backend,
+            lengths: SAMPLES.iter().map(|(name, bytes)| (*name, wav_length(bytes).unwrap_or(DEFAULT_LENGTH))).collect(),
+            playing: HashMap::new(),
+            music: vec![],
+            ducked: false,
+        }
+    }
+
+    /// the macroquad backend with the samples and the music `tracks` if sound is enabled, otherwise the null backend
+    pub async fn load(tracks: &[String]) -> Self {
+        if sound_enabled() {
+            Self::new(Box::new(MacroquadBackend::load(tracks).await))
+        } else {
+            println!("[INFO] Sound is off");
+            Self::new(Box::new(NullBackend))
+        }
+    }
+
+    /// play `sfx` at the effect volume of `settings`, unless its sample is already playing too often
+    pub fn play(&mut self, sfx: Sfx, settings: &Settings) {
+        let (name, volume) = sfx.sample();
+        let volume = volume * settings.effective_sfx_volume();
+        if volume <= 0. {
+            return;
+        }
+        let now = macroquad::miniquad::date::now();
+        let playing = self.playing.entry(name).or_default();
+        playing.retain(|end| *end > now);
+        if playing.len() >= MAX_INSTANCES {
+            return;
+        }
+        playing.push(now + self.lengths.get(name).copied().unwrap_or(DEFAULT_LENGTH));
+        self.backend.play(name, volume);
+    }
+
+    /// play the cues of the events of a tick
+    pub fn play_events(&mut self, events: &[WorldEvent], settings: &Settings) {
+        for event in events {
+            if let Some(sfx) = Sfx::from_event(event) {
+                self.play(sfx, settings);
+            }
+        }
+    }
+
+    /// switch to the music `track`, crossfading from the current one over `fade` seconds.
+    /// Nothing happens if it's already playing. An empty `track` fades the music out
+    pub fn play_music(&mut self, track: &str, fade: f32) {
+        if self.music.last().is_some_and(|channel| channel.track == track && channel.target > 0.) {
+            return;
+        }
+        self.stop_music(fade);
+        if track.is_empty() {
+            return;
+        }
+        let fade_speed = if fade > 0. { 1. / fade } else { f32::INFINITY };
+        // a track that's still fading out fades back in instead of starting over
+        if let Some(index) = self.music.iter().position(|channel| channel.track == track) {
+            let mut channel = self.music.remove(index);
+            channel.target = 1.;
+            self.music.push(channel);
+            return;
+        }
+        self.backend.play_looped(track, 0.);
+        self.music.push(Channel {
+            track: track.to_string(),
+            level: if fade > 0. { 0. } else { 1. },
+            target: 1.,
+            fade_speed,
+            volume: 0.,
+        });
+    }
+
+    /// fade out every track over `fade` seconds
+    pub fn stop_music(&mut self, fade: f32) {
+        let fade_speed = if fade > 0. { 1. / fade } else { f32::INFINITY };
+        for channel in &mut self.music {
+            channel.target = 0.;
+            channel.fade_speed = fade_speed;
+        }
+    }
+
+    /// turn the music down (or back up)
+    pub fn set_ducked(&mut self, ducked: bool) {
+        self.ducked = ducked;
+    }
+
+    /// fade the music and follow the music volume of `settings`. Call once per frame
+    pub fn update_music(&mut self, frame_time: f32, settings: &Settings) {
+        let volume = settings.effective_music_volume() * if self.ducked { DUCK_VOLUME } else { 1. };
+        for channel in &mut self.music {
+            let step = channel.fade_speed * frame_time;
+            channel.level = if channel.level < channel.target {
+                (channel.level + step).min(channel.target)
+            } else {
+                (channel.level - step).max(channel.target)
+            };
+            if channel.level * volume != channel.volume {
+                channel.volume = channel.level * volume;
+                self.backend.set_volume(&channel.track, channel.volume);
+            }
+        }
+        let backend = &mut self.backend;
+        self.music.retain(|channel| {
+            let faded_out = channel.level <= 0. && channel.target <= 0.;
+            if faded_out {
+                backend.stop(&channel.track);
+            }
+            !faded_out
+        });
+    }
+}
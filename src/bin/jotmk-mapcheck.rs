@@ -0,0 +1,185 @@
+/* J O T M K   M A P C H E C K
+ * -=-=-=-=-=-=-=-=-=-=-=-=-=-=-
+ * checks every map of the level list for the mistakes that break stages:
+ * missing layers, missing tilesets, the player spawn in a wall, spawnpoints
+ * enclosed by walls, exits the player can't walk to and exits leading to stages
+ * that don't exist. Tile properties of the tilesets are taken into account.
+ * Stages with a generated room get the room the game generates checked
+ *
+ * usage: jotmk-mapcheck [levels.json] [assets dir]
+ */
+use std::collections::VecDeque;
+use std::path::Path;
+use std::process::ExitCode;
+use nanoserde::DeJson;
+use top_down_game::{GAME_SCREEN_MAIN, TILE_SIZE};
+use top_down_game::collision::find_spawnpoints;
+use top_down_game::generator;
+use top_down_game::objects::find_objects;
+use top_down_game::player::Player;
+use top_down_game::stage::{Exit, Levels};
+use top_down_game::terrain::Terrain;
+use top_down_game::tiled::TiledMap;
+
+/// everything wrong with one map
+fn check_map(map: &TiledMap, edges: &[Exit], assets_dir: &Path) -> Vec<String> {
+    // the same checks the game does before it uses a map
+    let mut errors = map.problems();
+
+    if map.layer("spawnable").is_none() {
+        errors.push("missing tile layer `spawnable`".to_string());
+    }
+    for tileset in &map.tilesets {
+        if !tileset.image.is_empty() && !assets_dir.join(&tileset.image).exists() {
+            errors.push(format!("tileset image `{}` doesn't exist in {} (paths are relative to the map)", tileset.image, assets_dir.display()));
+        }
+    }
+    let room = ((GAME_SCREEN_MAIN.w / TILE_SIZE) as u32, (GAME_SCREEN_MAIN.h / TILE_SIZE) as u32);
+    if (map.width, map.height) != room {
+        errors.push(format!("map is {}x{} tiles, the room is {}x{}", map.width, map.height, room.0, room.1));
+    }
+    let Some(main) = map.layer("main") else {
+        return errors;
+    };
+    if main.data.len() != (map.width * map.height) as usize {
+        errors.push(format!("layer `main` has {} tiles instead of {}", main.data.len(), map.width * map.height));
+        return errors;
+    }
+
+    // walk the collision grid from the player spawn, like the flow field does. The player starts at
+    // the `player_start` object, or in the centre. Destructible tiles can be shot away, so they don't count
+    let terrain = Terrain::from_tiled(map);
+    let solid = |x: u32, y: u32| terrain.tile(x, y).is_wall();
+    let objects = find_objects(map);
+    let start = objects.player_start.unwrap_or(Player::default_start()) + Player::default().wh / 2.;
+    let spawn = ((start.x / TILE_SIZE) as u32, (start.y / TILE_SIZE) as u32);
+    if spawn.0 >= map.width || spawn.1 >= map.height {
+        errors.push(format!("the player starts outside of the map at tile {}/{}", spawn.0, spawn.1));
+        return errors;
+    }
+    if solid(spawn.0, spawn.1) {
+        errors.push(format!("the player spawns in a wall at tile {}/{}", spawn.0, spawn.1));
+        return errors;
+    }
+    let mut reachable = vec![false; (map.width * map.height) as usize];
+    let mut queue = VecDeque::new();
+    reachable[(spawn.1 * map.width + spawn.0) as usize] = true;
+    queue.push_back(spawn);
+    while let Some((x, y)) = queue.pop_front() {
+        for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if nx < 0 || ny < 0 || nx >= map.width as i32 || ny >= map.height as i32 {
+                continue;
+            }
+            let (nx, ny) = (nx as u32, ny as u32);
+            let index = (ny * map.width + nx) as usize;
+            if !reachable[index] && !solid(nx, ny) {
+                reachable[index] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    let is_reachable = |x: u32, y: u32| reachable[(y * map.width + x) as usize];
+
+    if map.layer("spawnable").is_some() {
+        let spawnpoints = find_spawnpoints(map);
+        for spawnpoint in &spawnpoints {
+            let (x, y) = ((spawnpoint.x / TILE_SIZE) as u32, (spawnpoint.y / TILE_SIZE) as u32);
+            if x >= map.width || y >= map.height {
+                continue;
+            } else if solid(x, y) {
+                errors.push(format!("spawnpoint at tile {}/{} is inside a wall", x, y));
+            } else if !is_reachable(x, y) {
+                errors.push(format!("spawnpoint at tile {}/{} is enclosed by walls, enemies can't get to the player", x, y));
+            }
+        }
+        if spawnpoints.is_empty() {
+            errors.push("layer `spawnable` doesn't have any spawnpoints".to_string());
+        }
+    }
+
+    // exit zones replace the edge exits
+    if !objects.exits.is_empty() {
+        for zone in &objects.exits {
+            let zone = zone.rect;
+            let (x1, y1) = ((zone.x / TILE_SIZE) as u32, (zone.y / TILE_SIZE) as u32);
+            let (x2, y2) = (((zone.x + zone.w.max(1.) - 1.) / TILE_SIZE) as u32, ((zone.y + zone.h.max(1.) - 1.) / TILE_SIZE) as u32);
+            let tiles = (y1..=y2.min(map.height - 1)).flat_map(|y| (x1..=x2.min(map.width - 1)).map(move |x| (x, y)));
+            if !tiles.into_iter().any(|(x, y)| is_reachable(x, y)) {
+                errors.push(format!("the player can't get from tile {}/{} to the exit zone at tile {}/{}", spawn.0, spawn.1, x1, y1));
+            }
+        }
+        return errors;
+    }
+    for exit in edges {
+        let edge: Vec<(u32, u32)> = match exit {
+            Exit::North => (0..map.width).map(|x| (x, 0)).collect(),
+            Exit::East => (0..map.height).map(|y| (map.width - 1, y)).collect(),
+            Exit::West => (0..map.height).map(|y| (0, y)).collect(),
+            Exit::South => (0..map.width).map(|x| (x, map.height - 1)).collect(),
+        };
+        if !edge.iter().any(|(x, y)| is_reachable(*x, *y)) {
+            errors.push(format!("no walkable exit at the {} edge: the player can't get from tile {}/{} to any of its tiles", exit.name(), spawn.0, spawn.1));
+        }
+    }
+    errors
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let levels_path = args.get(1).map(|arg| arg.as_str()).unwrap_or("levels.json");
+    let assets_dir = Path::new(args.get(2).map(|arg| arg.as_str()).unwrap_or("assets"));
+
+    let levels = match std::fs::read_to_string(levels_path).map_err(|err| err.to_string()).and_then(|json| Levels::deserialize_json(&json).map_err(|err| err.to_string())) {
+        Ok(levels) => levels,
+        Err(err) => {
+            println!("{}: {}", levels_path, err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut broken = 0;
+    for (index, stage) in levels.stages.iter().enumerate() {
+        let path = assets_dir.join(stage.map_path());
+        let json = match stage.seed {
+            Some(seed) if stage.map.is_empty() => Ok(generator::generate(seed).to_tiled_json()),
+            _ => std::fs::read_to_string(&path),
+        };
+        let errors = match json {
+            Err(err) => vec![format!("can't read the map: {}", err)],
+            Ok(json) => match TiledMap::deserialize_json(&json) {
+                Err(err) => vec![format!("not a Tiled JSON map: {}", err)],
+                Ok(map) => {
+                    let mut errors = check_map(&map, &stage.edges(), assets_dir);
+                    if !stage.exit.is_empty() && Exit::from_name(&stage.exit).is_none() {
+                        errors.push(format!("unknown exit `{}` in {}, use north, east, south or west", stage.exit, levels_path));
+                    }
+                    for (edge, target) in &stage.exits {
+                        if Exit::from_name(edge).is_none() {
+                            errors.push(format!("unknown exit `{}` in {}, use north, east, south or west", edge, levels_path));
+                        }
+                        if target != "end" && !levels.stages.iter().any(|other| other.id() == *target) {
+                            errors.push(format!("the {} exit leads to stage `{}`, which isn't in {}", edge, target, levels_path));
+                        }
+                    }
+                    errors
+                }
+            },
+        };
+        if errors.is_empty() {
+            println!("stage {} ({}): OK", index + 1, path.display());
+        } else {
+            broken += 1;
+            for error in errors {
+                println!("stage {} ({}): error: {}", index + 1, path.display(), error);
+            }
+        }
+    }
+
+    if broken > 0 {
+        println!("{} of {} maps are broken", broken, levels.stages.len());
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,30 @@
+/* J O T M K   M A P G E N
+ * -=-=-=-=-=-=-=-=-=-=-=-=-
+ * writes the room the game generates from a seed as a Tiled JSON map, so it
+ * can be looked at (and fixed up) in Tiled. The tileset path is relative to
+ * the map, so put it next to `tilemap.png`
+ *
+ * usage: jotmk-mapgen <seed> [output file, default assets/generated-<seed>.json]
+ */
+use std::process::ExitCode;
+use top_down_game::generator;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(seed) = args.get(1).and_then(|arg| arg.parse::<u64>().ok()) else {
+        println!("usage: jotmk-mapgen <seed> [output file]");
+        return ExitCode::FAILURE;
+    };
+    let path = args.get(2).cloned().unwrap_or_else(|| format!("assets/{}", generator::map_path(seed)));
+
+    match std::fs::write(&path, generator::generate(seed).to_tiled_json()) {
+        Ok(()) => {
+            println!("room {} written to {}", seed, path);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            println!("{}: {}", path, err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use std::f32::consts::TAU;
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+use crate::{GAME_SCREEN_MAIN, TILE_SIZE};
+use crate::bullet::{Bullet, BULLET_SPEED};
+use crate::collision::CollisionType;
+use crate::enemy::Enemy;
+use crate::world::World;
+
+pub const BOSS_HP: i32 = 60;
+/// number of phases the boss' HP is split into evenly. Every phase attacks more often and knows one more attack
+const PHASE_COUNT: i32 = 3;
+/// ticks the boss stands still before charging
+const CHARGE_WINDUP: i32 = 40;
+const CHARGE_SPEED: f32 = 3.;
+const BULLET_RING_SIZE: usize = 12;
+
+/// what the boss is currently doing
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Attack {
+    /// walk towards the player until the next attack
+    Walk,
+    /// stand still and flash, then run in a straight line until hitting a wall
+    Charge { windup: i32, direction: Vec2 },
+    /// shoot a ring of bullets in every direction
+    BulletRing,
+    /// call some minions
+    Summon,
+}
+
+#[derive(Clone)]
+pub struct Boss {
+    pub coords: Vec2,
+    pub wh: Vec2,
+    pub hp: i32,
+    pub max_hp: i32,
+    pub attack: Attack,
+    /// ticks until the next attack
+    attack_timer: i32,
+    /// ticks the damage animation is still shown
+    pub hurt_timer: i32,
+    speed: f32,
+}
+
+impl Boss {
+    pub fn new() -> Self {
+        let mut boss = Self {
+            coords: vec2(0., 0.),
+            wh: vec2(TILE_SIZE * 2., TILE_SIZE * 2.),
+            hp: BOSS_HP,
+            max_hp: BOSS_HP,
+            attack: Attack::Walk,
+            attack_timer: 120,
+            hurt_timer: 0,
+            speed: 0.4,
+        };
+        boss.reset_coords();
+        boss
+    }
+
+    /// put the boss back at the top of the room
+    pub fn reset_coords(&mut self) {
+        self.coords = vec2(GAME_SCREEN_MAIN.w / 2. - self.wh.x / 2., TILE_SIZE * 2.);
+        self.attack = Attack::Walk;
+        self.attack_timer = 120;
+    }
+
+    /// 0 at full health, up to `PHASE_COUNT - 1` when it's nearly dead
+    pub fn phase(&self) -> i32 {
+        ((self.max_hp - self.hp) * PHASE_COUNT / self.max_hp).clamp(0, PHASE_COUNT - 1)
+    }
+
+    pub fn hitbox(&self) -> Rect {
+        Rect::new(self.coords.x, self.coords.y, self.wh.x, self.wh.y)
+    }
+
+    /// take damage. If the boss dies, returns true, else it returns false
+    pub fn damage(&mut self, damage: i32) -> bool {
+        self.hp -= damage;
+        self.hurt_timer = 6;
+        self.hp <= 0
+    }
+
+    /// pick the next attack. Later phases know more attacks and attack more often
+    fn next_attack(&mut self, world: &World) {
+        let phase = self.phase();
+        self.attack = match gen_range(0, phase + 1) {
+            0 => Attack::Charge { windup: CHARGE_WINDUP, direction: self.charge_direction(world) },
+            1 => Attack::BulletRing,
+            _ => Attack::Summon,
+        };
+        self.attack_timer = 180 - phase * 50;
+    }
+
+    /// towards the player (or the decoy), or anywhere if the boss stands right on it
+    fn charge_direction(&self, world: &World) -> Vec2 {
+        let target = world.player.target() + world.player.wh / 2.;
+        let direction = (target - (self.coords + self.wh / 2.)).normalize_or_zero();
+        if direction == Vec2::ZERO {
+            Vec2::from_angle(gen_range(0., TAU))
+        } else {
+            direction
+        }
+    }
+
+    /// update (fixed) for the boss
+    pub fn update(&mut self, world: &mut World) {
+        if self.hurt_timer > 0 {
+            self.hurt_timer -= 1;
+        }
+        match self.attack {
+            Attack::Walk => {
+                let centre = self.coords + self.wh / 2.;
+                let target = world.player.target() + world.player.wh / 2.;
+                let velocity = (target - centre).normalize_or_zero() * (self.speed + self.phase() as f32 * 0.2);
+                self.move_by(velocity, &world.collision_map);
+                self.attack_timer -= 1;
+                if self.attack_timer <= 0 {
+                    self.next_attack(world);
+                }
+            }
+            Attack::Charge { windup, direction } => {
+                if windup > 0 {
+                    // aim at the player until the very last moment
+                    let direction = self.charge_direction(world);
+                    self.attack = Attack::Charge { windup: windup - 1, direction };
+                } else if !self.move_by(direction * CHARGE_SPEED, &world.collision_map) {
+                    self.attack = Attack::Walk;
+                }
+            }
+            Attack::BulletRing => {
+                let centre = self.coords + self.wh / 2.;
+                // turn every ring a bit so there's no safe spot
+                let offset = gen_range(0., TAU);
+                for i in 0..BULLET_RING_SIZE {
+                    let angle = offset + i as f32 * TAU / BULLET_RING_SIZE as f32;
+                    world.bullets.push(Bullet::hostile(centre, Vec2::from_angle(angle) * BULLET_SPEED * 0.6));
+                }
+                self.attack = Attack::Walk;
+            }
+            Attack::Summon => {
+                for _ in 0..2 + self.phase() {
+                    let kind = world.enemy_kinds.random();
+                    let offset = vec2(gen_range(-TILE_SIZE, TILE_SIZE), TILE_SIZE * 2.);
+                    // minions that would end up in a wall don't come
+                    let minion = Enemy::new(kind, self.coords + offset, &world.enemy_kinds);
+                    if world.can_spawn(&minion) {
+                        world.enemies.push(minion);
+                    }
+                }
+                self.attack = Attack::Walk;
+            }
+        }
+    }
+
+    /// move the boss, one axis at a time. Returns false if it bumped into a wall or the edge of the room
+    fn move_by(&mut self, velocity: Vec2, collision_map: &[CollisionType]) -> bool {
+        let mut moved = true;
+        self.coords.x += velocity.x;
+        if self.collides(collision_map) {
+            self.coords.x -= velocity.x;
+            moved = false;
+        }
+        self.coords.y += velocity.y;
+        if self.collides(collision_map) {
+            self.coords.y -= velocity.y;
+            moved = false;
+        }
+        moved
+    }
+
+    fn collides(&self, collision_map: &[CollisionType]) -> bool {
+        if self.coords.x < 0. || self.coords.y < 0. || self.coords.x + self.wh.x > GAME_SCREEN_MAIN.w || self.coords.y + self.wh.y > GAME_SCREEN_MAIN.h {
+            return true;
+        }
+        let hitbox = Rect::new(self.coords.x + 2., self.coords.y + 2., self.wh.x - 4., self.wh.y - 4.);
+        collision_map.iter().any(|tile| {
+            if let CollisionType::Solid(x, y) = tile {
+                hitbox.overlaps(&Rect::new(*x as f32 * TILE_SIZE, *y as f32 * TILE_SIZE, TILE_SIZE, TILE_SIZE))
+            } else {
+                false
+            }
+        })
+    }
+
+    pub fn draw(&self, texture: &Texture2D) {
+        let color = if self.hurt_timer > 0 {
+            RED
+        } else if let Attack::Charge { windup, .. } = self.attack {
+            // flash while winding up
+            if windup > 0 && (windup / 5) % 2 == 0 { ORANGE } else { WHITE }
+        } else {
+            WHITE
+        };
+        draw_texture_ex(texture, self.coords.x + GAME_SCREEN_MAIN.x, self.coords.y + GAME_SCREEN_MAIN.y, color, DrawTextureParams {
+            dest_size: Some(self.wh),
+            ..Default::default()
+        });
+    }
+
+    /// the boss' health bar at the top of the room
+    pub fn draw_health_bar(&self) {
+        let width = GAME_SCREEN_MAIN.w - 32.;
+        draw_rectangle(GAME_SCREEN_MAIN.x + 16., GAME_SCREEN_MAIN.y + 4., width, 4., DARKGRAY);
+        draw_rectangle(GAME_SCREEN_MAIN.x + 16., GAME_SCREEN_MAIN.y + 4., width * self.hp.max(0) as f32 / self.max_hp as f32, 4., RED);
+    }
+}
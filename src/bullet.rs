@@ -1,45 +1,138 @@
 use macroquad::prelude::*;
-use crate::{GameState, GAME_SCREEN_MAIN};
+use crate::GAME_SCREEN_MAIN;
+use crate::terrain::Terrain;
+
+/// who fired a bullet, and therefore who it can hurt
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Faction {
+    Player,
+    Enemy,
+}
 
 #[derive(PartialEq, Clone)]
 pub struct Bullet {
     pub coords: Vec2,
     pub velocity: Vec2,
+    pub faction: Faction,
+    /// piercing bullets don't get destroyed when they hit an enemy
+    pub piercing: bool,
+    /// ticks until a piercing bullet can hit again, so it doesn't hit the same enemy every tick
+    pub pierce_cooldown: i32,
+    /// walls the bullet can still bounce off before it gets destroyed
+    pub bounces: i32,
 }
 
-const BULLET_SPEED: f32 = 2.0;
+pub const BULLET_SPEED: f32 = 2.0;
+/// angle between the bullets of a spread shot, in radians
+pub const SPREAD_ANGLE: f32 = 0.3;
+/// walls a bouncing bullet bounces off
+pub const MAX_BOUNCES: i32 = 3;
+/// longest distance a bullet moves between two collision checks, so fast bullets can't skip a wall
+const BULLET_STEP: f32 = 1.;
+/// ticks the impact effect is shown
+const IMPACT_TIME: i32 = 10;
 impl Bullet {
+    /// a player bullet fired from `coords` (the shooter's top left corner) in one of the eight directions
     pub fn new(coords: Vec2, dir: Direction) -> Self {
-        let mut velocity = vec2(0., 0.);
-
-        if dir == Direction::Up {
-            velocity = vec2(0., -BULLET_SPEED)
-        } else if dir == Direction::Down {
-            velocity = vec2(0., BULLET_SPEED)
-        } else if dir == Direction::Left {
-            velocity = vec2(-BULLET_SPEED, 0.)
-        } else if dir == Direction::Right {
-            velocity = vec2(BULLET_SPEED, 0.)
-        } else if dir == Direction::Leftup {
-            velocity = vec2(-BULLET_SPEED.sqrt(), -BULLET_SPEED.sqrt());
-        } else if dir == Direction::Leftdown {
-            velocity = vec2(-BULLET_SPEED.sqrt(), BULLET_SPEED.sqrt());
-        } else if dir == Direction::Rightup {
-            velocity = vec2(BULLET_SPEED.sqrt(), -BULLET_SPEED.sqrt());
-        } else if dir == Direction::Rightdown {
-            velocity = vec2(BULLET_SPEED.sqrt(), BULLET_SPEED.sqrt());
-        }
-
+        Self::with_velocity(coords, dir.vector() * BULLET_SPEED)
+    }
+    /// a player bullet flying at `angle` in radians: 0 is right and, as y points down, it turns clockwise
+    pub fn from_angle(coords: Vec2, angle: f32) -> Self {
+        Self::with_velocity(coords, Vec2::from_angle(angle) * BULLET_SPEED)
+    }
+    /// a player bullet flying towards `direction`, at any angle
+    pub fn aimed(coords: Vec2, direction: Vec2) -> Self {
+        Self::from_angle(coords, direction.y.atan2(direction.x))
+    }
+    fn with_velocity(coords: Vec2, velocity: Vec2) -> Self {
         Bullet {
             velocity,
             coords: coords + vec2(8., 8.),
+            faction: Faction::Player,
+            piercing: false,
+            pierce_cooldown: 0,
+            bounces: 0,
         }
     }
+    /// a bullet fired by an enemy from `coords` (its centre) that hurts the player
+    pub fn hostile(coords: Vec2, velocity: Vec2) -> Self {
+        Bullet {
+            velocity,
+            coords,
+            faction: Faction::Enemy,
+            piercing: false,
+            pierce_cooldown: 0,
+            bounces: 0,
+        }
+    }
+    pub fn piercing(mut self, piercing: bool) -> Self {
+        self.piercing = piercing;
+        self
+    }
+    pub fn bouncing(mut self, bouncing: bool) -> Self {
+        self.bounces = if bouncing { MAX_BOUNCES } else { 0 };
+        self
+    }
+    /// rotate the bullet's direction by `angle` radians
+    pub fn rotated(mut self, angle: f32) -> Self {
+        self.velocity = Vec2::from_angle(angle).rotate(self.velocity);
+        self
+    }
+    /// move the bullet in small steps, bouncing off or stopping at tiles that block bullets.
+    /// Returns true if it hit a wall and has to be destroyed; `coords` is then the point of impact
+    pub fn update(&mut self, terrain: &Terrain) -> bool {
+        if self.pierce_cooldown > 0 {
+            self.pierce_cooldown -= 1;
+        }
+        let steps = (self.velocity.length() / BULLET_STEP).ceil().max(1.) as i32;
+        for _ in 0..steps {
+            // each axis on its own, so we know which way to bounce
+            let step = self.velocity / steps as f32;
+            self.coords.x += step.x;
+            if terrain.blocks_bullets(self.coords) {
+                if self.bounces == 0 {
+                    return true;
+                }
+                self.coords.x -= step.x;
+                self.velocity.x = -self.velocity.x;
+                self.bounces -= 1;
+            }
+            self.coords.y += step.y;
+            if terrain.blocks_bullets(self.coords) {
+                if self.bounces == 0 {
+                    return true;
+                }
+                self.coords.y -= step.y;
+                self.velocity.y = -self.velocity.y;
+                self.bounces -= 1;
+            }
+        }
+        false
+    }
+    pub fn draw(&self) {
+        draw_circle(self.coords.x + GAME_SCREEN_MAIN.x, self.coords.y + GAME_SCREEN_MAIN.y, 2.0, if self.faction == Faction::Enemy { RED } else { DARKGRAY });
+    }
+}
+
+/// the little puff where a bullet hit a wall
+#[derive(Clone)]
+pub struct Impact {
+    pub coords: Vec2,
+    /// ticks until the effect is gone
+    pub timer: i32,
+}
+
+impl Impact {
+    pub fn new(coords: Vec2) -> Self {
+        Self { coords, timer: IMPACT_TIME }
+    }
     pub fn update(&mut self) {
-        self.coords += self.velocity;
+        self.timer -= 1;
     }
     pub fn draw(&self) {
-        draw_circle(self.coords.x + GAME_SCREEN_MAIN.x, self.coords.y + GAME_SCREEN_MAIN.y, 2.0, DARKGRAY);
+        let progress = 1. - self.timer as f32 / IMPACT_TIME as f32;
+        let color = Color::new(0.8, 0.8, 0.8, 1. - progress);
+        draw_circle_lines(self.coords.x + GAME_SCREEN_MAIN.x, self.coords.y + GAME_SCREEN_MAIN.y, 1. + progress * 3., 1., color);
     }
 }
 
@@ -53,4 +146,35 @@ pub enum Direction {
     Leftdown,
     Rightup,
     Rightdown,
-}
\ No newline at end of file
+}
+
+impl Direction {
+    /// unit vector pointing this way, so diagonal bullets are as fast as straight ones
+    pub fn vector(&self) -> Vec2 {
+        let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+        match self {
+            Direction::Up => vec2(0., -1.),
+            Direction::Down => vec2(0., 1.),
+            Direction::Left => vec2(-1., 0.),
+            Direction::Right => vec2(1., 0.),
+            Direction::Leftup => vec2(-diagonal, -diagonal),
+            Direction::Leftdown => vec2(-diagonal, diagonal),
+            Direction::Rightup => vec2(diagonal, -diagonal),
+            Direction::Rightdown => vec2(diagonal, diagonal),
+        }
+    }
+    /// the one of the eight directions closest to `vector`
+    pub fn from_vector(vector: Vec2) -> Self {
+        let octant = (vector.y.atan2(vector.x) / std::f32::consts::FRAC_PI_4).round() as i32;
+        match octant {
+            0 => Direction::Right,
+            1 => Direction::Rightdown,
+            2 => Direction::Down,
+            3 => Direction::Leftdown,
+            -1 => Direction::Rightup,
+            -2 => Direction::Up,
+            -3 => Direction::Leftup,
+            _ => Direction::Left,
+        }
+    }
+}
@@ -1,5 +1,26 @@
+use macroquad::prelude::*;
+use crate::TILE_SIZE;
+use crate::tiled::TiledMap;
+
+/// whether a tile blocks walking. Built from the `Terrain` of the room
+
 #[derive(PartialEq, Clone, Copy)]
 pub enum CollisionType {
     Solid(u32, u32),
     Empty(u32, u32),
-}
\ No newline at end of file
+}
+
+/// enemy spawn areas from the `spawnable` layer
+pub fn find_spawnpoints(map: &TiledMap) -> Vec<Vec2> {
+    let mut spawnpoints = vec![];
+    let Some(layer) = map.layer("spawnable") else {
+        return spawnpoints;
+    };
+    for (index, gid) in layer.data.iter().enumerate() {
+        if *gid != 0 {
+            let (x, y) = (index as u32 % map.width, index as u32 / map.width);
+            spawnpoints.push(vec2(x as f32 * TILE_SIZE + 1., y as f32 * TILE_SIZE + 1.));
+        }
+    }
+    spawnpoints
+}
@@ -0,0 +1,46 @@
+use crate::audio::STAGE_MUSIC;
+use crate::enemy::EnemyKinds;
+use crate::generator::{generate, map_path};
+use crate::stage::{Destination, Exit, Stage};
+
+/// ticks a wave lasts before the exits open
+const WAVE_TIMER: i32 = 1800;
+/// enemy kinds (in the order of the registry) in the first wave. Every second wave adds one
+const FIRST_KINDS: usize = 2;
+
+/// a run of the endless mode: a generated room per wave, each one harder than the last, until the player dies
+#[derive(Clone, Debug)]
+pub struct Endless {
+    /// the rooms are generated from `seed + wave`
+    pub seed: u64,
+    /// the current wave, counting from 0. Also the number of waves survived
+    pub wave: i32,
+}
+
+impl Endless {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, wave: 0 }
+    }
+
+    /// the room of the current wave
+    pub fn stage(&self, kinds: &EnemyKinds) -> Stage {
+        let seed = self.seed.wrapping_add(self.wave as u64);
+        let wave = self.wave as f32;
+        let unlocked = FIRST_KINDS + self.wave as usize / 2;
+        Stage {
+            map: generate(seed).to_tiled_json(),
+            map_path: map_path(seed),
+            timer: WAVE_TIMER,
+            // the spawn chance (1 in x per tick) goes down by 5 every wave, to at most one enemy every 12 ticks
+            spawn_rate: vec![(90. - wave * 5.).max(20.), (70. - wave * 5.).max(12.)],
+            enemies: kinds.kinds.iter().take(unlocked).map(|kind| kind.name.clone()).collect(),
+            kill_goal: 0,
+            // every door leads on, the world picks the next room itself
+            exits: Exit::ALL.iter().map(|exit| (*exit, Destination::Stage(0))).collect(),
+            boss: false,
+            enemy_health: 1. + wave * 0.2,
+            enemy_speed: (1. + wave * 0.04).min(1.6),
+            music: STAGE_MUSIC.to_string(),
+        }
+    }
+}
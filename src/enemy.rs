@@ -1,28 +1,152 @@
 use macroquad::prelude::*;
 use macroquad::rand::gen_range;
 use macroUtils::include_texture;
-use crate::{GameState, GAME_SCREEN_MAIN, TILE_SIZE};
-use crate::bullet::Direction;
+use nanoserde::DeJson;
+use crate::{GAME_SCREEN_MAIN, TILE_SIZE};
+use crate::bullet::{Bullet, Direction, BULLET_SPEED};
 use crate::collision::CollisionType;
+use crate::pathfinding::FlowField;
 use crate::player::Player;
+use crate::terrain::Terrain;
 
-#[derive(Copy, Clone, PartialEq)]
-enum HorizontalVertical {
-    Horizontal,
-    Vertical,
+fn default_fire_rate() -> i32 {
+    60
 }
 
-static mut TEXTURES: Vec<Texture2D> = vec![];
-pub async fn initialize_enemy_textures() {
-    unsafe {
-        // ghost
-        TEXTURES.push(include_texture!("../assets/enemy.png"));
+/// file the enemy kinds are read from. If it doesn't exist, the built-in kinds are used
+pub const ENEMY_KINDS_PATH: &str = "enemies.json";
+/// the shipped sprites, used when they can't be read from the working directory
+const BUILTIN_SPRITES: [(&str, &[u8]); 5] = [
+    ("assets/enemy.png", include_bytes!("../assets/enemy.png")),
+    ("assets/orc.png", include_bytes!("../assets/orc.png")),
+    ("assets/bat.png", include_bytes!("../assets/bat.png")),
+    ("assets/slime.png", include_bytes!("../assets/slime.png")),
+    ("assets/wizard.png", include_bytes!("../assets/wizard.png")),
+];
+
+/// how an enemy moves
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Behaviour {
+    /// walk towards the player, around walls
+    Chase,
+    /// chase the player, but sometimes wander off in a random direction
+    Erratic,
+    /// fly straight towards the player, over walls
+    Fly,
+    /// walk towards the player until it's in range, then stand still and shoot
+    Ranged,
+}
+
+impl Behaviour {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "chase" => Some(Behaviour::Chase),
+            "erratic" => Some(Behaviour::Erratic),
+            "fly" => Some(Behaviour::Fly),
+            "ranged" => Some(Behaviour::Ranged),
+            _ => None,
+        }
     }
 }
 
-pub fn get_texture<'a>(id: usize) -> &'a Texture2D {
-    unsafe {
-        TEXTURES.get(id).unwrap()
+/// one entry of the enemy kind registry
+#[derive(DeJson, Clone)]
+pub struct EnemyKind {
+    pub name: String,
+    /// path of the texture, relative to the working directory
+    pub sprite: String,
+    pub hp: i32,
+    pub speed: f32,
+    pub width: f32,
+    pub height: f32,
+    /// `chase`, `erratic`, `fly` or `ranged`
+    pub behaviour: String,
+    /// distance from which ranged enemies start shooting
+    #[nserde(default)]
+    pub range: f32,
+    /// ticks between two shots of a ranged enemy
+    #[nserde(default_with = "default_fire_rate")]
+    pub fire_rate: i32,
+    /// points the player gets for killing it
+    pub score: i32,
+    /// how likely this kind spawns compared to the others
+    pub spawn_weight: i32,
+}
+
+/// the enemy kind registry
+#[derive(DeJson, Clone)]
+pub struct EnemyKinds {
+    pub kinds: Vec<EnemyKind>,
+}
+
+impl EnemyKinds {
+    /// load the enemy kinds from `ENEMY_KINDS_PATH`, falling back to the built-in ones
+    pub fn load() -> Self {
+        let builtin = include_str!("../enemies.json");
+        let json = std::fs::read_to_string(ENEMY_KINDS_PATH).unwrap_or_else(|_| builtin.to_string());
+        let kinds = match EnemyKinds::deserialize_json(&json) {
+            Ok(kinds) if kinds.kinds.is_empty() => {
+                println!("WARN: {} doesn't have any enemy kinds. Using the built-in enemies", ENEMY_KINDS_PATH);
+                EnemyKinds::deserialize_json(builtin).unwrap()
+            }
+            Ok(kinds) => kinds,
+            Err(err) => {
+                println!("WARN: couldn't parse {}: {}. Using the built-in enemies", ENEMY_KINDS_PATH, err);
+                EnemyKinds::deserialize_json(builtin).unwrap()
+            }
+        };
+        for kind in &kinds.kinds {
+            if Behaviour::from_name(&kind.behaviour).is_none() {
+                println!("WARN: enemy `{}` has unknown behaviour `{}`, it will chase the player", kind.name, kind.behaviour);
+            }
+        }
+        kinds
+    }
+
+    /// load the sprite of every kind, in the same order as `kinds`. The shipped sprites are built in,
+    /// so they're found from any working directory
+    pub fn load_textures(&self) -> Vec<Texture2D> {
+        self.kinds.iter().map(|kind| {
+            let builtin = BUILTIN_SPRITES.iter().find(|(path, _)| *path == kind.sprite);
+            match (std::fs::read(&kind.sprite), builtin) {
+                (Ok(bytes), _) => Texture2D::from_file_with_format(&bytes, None),
+                (Err(_), Some((_, bytes))) => Texture2D::from_file_with_format(bytes, None),
+                (Err(err), None) => {
+                    println!("WARN: couldn't load sprite {} of enemy `{}`: {}", kind.sprite, kind.name, err);
+                    include_texture!("../assets/enemy.png")
+                }
+            }
+        }).collect()
+    }
+
+    /// index of the kind called `name`
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.kinds.iter().position(|kind| kind.name == name)
+    }
+
+    /// index of a random kind, weighted by `spawn_weight`
+    pub fn random(&self) -> usize {
+        self.random_of(&[])
+    }
+
+    /// index of a random kind out of the ones called `names`, weighted by `spawn_weight`.
+    /// Any kind if `names` is empty or none of them exist
+    pub fn random_of(&self, names: &[String]) -> usize {
+        let allowed = |kind: &EnemyKind| names.is_empty() || names.contains(&kind.name);
+        let candidates: Vec<usize> = (0..self.kinds.len()).filter(|index| allowed(&self.kinds[*index])).collect();
+        let candidates = if candidates.is_empty() { (0..self.kinds.len()).collect() } else { candidates };
+        let total: i32 = candidates.iter().map(|index| self.kinds[*index].spawn_weight.max(0)).sum();
+        if total <= 0 {
+            return candidates[gen_range(0, candidates.len())];
+        }
+        let mut roll = gen_range(0, total);
+        for index in &candidates {
+            roll -= self.kinds[*index].spawn_weight.max(0);
+            if roll < 0 {
+                return *index;
+            }
+        }
+        candidates[0]
     }
 }
 
@@ -32,150 +156,128 @@ pub struct Enemy {
     velocity: Vec2,
     speed: f32,
     pub wh: Vec2,
-    preferred_direction: HorizontalVertical,
     hp: i32,
-    pub last_blocked: bool,
-    ldir: Direction,
+    /// index into `EnemyKinds::kinds`
+    pub kind: usize,
+    behaviour: Behaviour,
+    /// ticks an erratic enemy keeps wandering
+    wander_timer: i32,
+    wander_direction: Vec2,
+    range: f32,
+    fire_rate: i32,
+    /// ticks until a ranged enemy can shoot again
+    shoot_cooldown: i32,
 }
 
 impl Enemy {
-    pub fn new_random(spawnpoints: &Vec<Vec2>) -> Self {
+    /// an enemy of one of the kinds called `names` (any kind if empty) at a random spawnpoint
+    pub fn new_random(spawnpoints: &[Vec2], names: &[String], kinds: &EnemyKinds) -> Self {
         let index = gen_range(0, spawnpoints.len());
-        let preferred_direction = {
-            let vertical = gen_range(0, 2);
-            if vertical == 1 {
-                HorizontalVertical::Vertical
-            } else {
-                HorizontalVertical::Horizontal
-            }
-        };
+        Self::new(kinds.random_of(names), spawnpoints[index], kinds)
+    }
+
+    pub fn new(kind: usize, coords: Vec2, kinds: &EnemyKinds) -> Self {
+        let kind_info = &kinds.kinds[kind];
         Self {
-            coords: spawnpoints.get(index).unwrap().clone(),
+            coords,
             velocity: vec2(0., 0.),
-            speed: 0.6,
-            wh: vec2(15., 15.),
-            preferred_direction,
-            hp: 2,
-            last_blocked: false,
-            ldir: Direction::Left,
+            speed: kind_info.speed,
+            wh: vec2(kind_info.width, kind_info.height),
+            hp: kind_info.hp,
+            kind,
+            behaviour: Behaviour::from_name(&kind_info.behaviour).unwrap_or(Behaviour::Chase),
+            wander_timer: 0,
+            wander_direction: vec2(0., 0.),
+            range: kind_info.range,
+            // a rate of 0 would shoot every tick
+            fire_rate: kind_info.fire_rate.max(1),
+            shoot_cooldown: kind_info.fire_rate.max(1),
         }
     }
 
+    /// multiply the health and speed of the enemy, for harder stages
+    pub fn scaled(mut self, health: f32, speed: f32) -> Self {
+        self.hp = ((self.hp as f32 * health).round() as i32).max(1);
+        self.speed *= speed;
+        self
+    }
+
     /// take damage. If the enemy dies, returns true, else it returns false
     pub fn damage(&mut self, damage: i32) -> bool {
         self.hp -= damage;
-        if self.hp <= 0 {
-            true
-        } else {
-            false
-        }
+        self.hp <= 0
     }
 
-    /// update (fixed) for enemies
-    /// # TODO: fix enemies studder at corners
-    pub fn update(&mut self, player: &Player, collision_map: &Vec<CollisionType>) {
-
-        // movement, "AI"
-        // direction to go. Every enemy can prefer either horizontal or vertical movement
-        let mut godir: Direction = Direction::Down;
-        if self.preferred_direction == HorizontalVertical::Horizontal {
-            if player.coords.x.round() > self.coords.x.round() {
-                godir = Direction::Right;
-            } else if player.coords.x.round() < self.coords.x.round() {
-                godir = Direction::Left;
-            } else if player.coords.y.round() > self.coords.y.round() {
-                godir = Direction::Down;
-            } else if player.coords.y.round() < self.coords.y.round() {
-                godir = Direction::Up;
-            } else {
-                godir = Direction::Down;
-            }
-        } else {
-            if player.coords.y.round() > self.coords.y.round() {
-                godir = Direction::Down;
-            } else if player.coords.y.round() < self.coords.y.round() {
-                godir = Direction::Up;
-            } else if player.coords.x.round() > self.coords.x.round() {
-                godir = Direction::Right;
-            } else if player.coords.x.round() < self.coords.x.round() {
-                godir = Direction::Left;
-            } else {
-                godir = Direction::Down;
-            }
+    /// update (fixed) for enemies. Returns the bullet if a ranged enemy shot
+    pub fn update(&mut self, player: &Player, collision_map: &[CollisionType], terrain: &Terrain, flow_field: &FlowField) -> Option<Bullet> {
+        // the player, or wherever the enemies think the player is
+        let target = player.target() + player.wh / 2.;
+        let centre = self.coords + self.wh / 2.;
+
+        // flying enemies don't care about walls
+        if self.behaviour == Behaviour::Fly {
+            self.velocity = (target - centre).normalize_or_zero() * self.speed;
+            self.coords += self.velocity;
+            return None;
         }
-        // check if way is blocked. If it is, go the other direction.
-        if godir == Direction::Left || godir == Direction::Right {
-            if get_tile_collisionmap(&self.coords, &godir, collision_map, self.speed) || (self.last_blocked && self.ldir == godir) {
-                if player.coords.y.round() >= self.coords.y.round() {
-                    godir = Direction::Down;
-                } else {
-                    godir = Direction::Up;
-                }
+
+        // ranged enemies stop once the player is in range and shoot along the closest of the eight directions
+        if self.behaviour == Behaviour::Ranged {
+            if self.shoot_cooldown > 0 {
+                self.shoot_cooldown -= 1;
             }
-        } else if godir == Direction::Up || godir == Direction::Down {
-            if get_tile_collisionmap(&self.coords, &godir, collision_map, self.speed) || (self.last_blocked && self.ldir == godir) {
-                if player.coords.x.round() >= self.coords.x.round() {
-                    godir = Direction::Right;
-                } else {
-                    godir = Direction::Left;
+            if centre.distance(target) <= self.range {
+                self.velocity = vec2(0., 0.);
+                if self.shoot_cooldown == 0 {
+                    self.shoot_cooldown = self.fire_rate;
+                    return Some(Bullet::hostile(centre, Direction::from_vector(target - centre).vector() * BULLET_SPEED));
                 }
+                return None;
             }
         }
-        // perform velocity changes
-        if godir == Direction::Left {
-            self.velocity = vec2(-self.speed, 0.);
-        } else if godir == Direction::Right {
-            self.velocity = vec2(self.speed, 0.);
-        } else if godir == Direction::Down {
-            self.velocity = vec2(0., self.speed);
-        } else if godir == Direction::Up {
-            self.velocity = vec2(0., -self.speed);
+
+        // erratic enemies sometimes wander off for a bit
+        if self.behaviour == Behaviour::Erratic && self.wander_timer == 0 && gen_range(0, 90) == 0 {
+            self.wander_timer = 40;
+            self.wander_direction = [vec2(0., -1.), vec2(0., 1.), vec2(-1., 0.), vec2(1., 0.)][gen_range(0, 4)];
         }
-        self.ldir = godir;
 
-        // collision detection, final movement
-        self.last_blocked = false;        // if needed, this gets updated later
-        let old_coords = self.coords;
-        self.coords += self.velocity;
-        for tile in collision_map {
-            if let CollisionType::Solid(x, y) = tile {
-                if Rect::new(self.coords.x + 1., self.coords.y + 1., self.wh.x - 3., self.wh.y - 3.).overlaps(&Rect::new(*x as f32 * TILE_SIZE, *y as f32 * TILE_SIZE, TILE_SIZE, TILE_SIZE)) {
-                    self.coords = old_coords;
-                    self.last_blocked = true;
-                }
-            }
+        // movement, "AI": follow the flow field towards the player's tile, then go straight for the player
+        let direction = if self.wander_timer > 0 {
+            self.wander_timer -= 1;
+            self.wander_direction
+        } else if let Some(next_tile) = flow_field.next_step(centre) {
+            next_tile - centre
+        } else {
+            target - centre
+        };
+        self.velocity = direction.normalize_or_zero() * self.speed * terrain.slow_at(centre);
+
+        // collision detection, final movement. Each axis on its own, so enemies slide along walls instead of getting stuck
+        self.coords.x += self.velocity.x;
+        if self.collides(collision_map) {
+            self.coords.x -= self.velocity.x;
         }
+        self.coords.y += self.velocity.y;
+        if self.collides(collision_map) {
+            self.coords.y -= self.velocity.y;
+        }
+        None
     }
-    /// draw the enemy. If `takes_damage` is true, show damage animation
-    pub fn draw(&self, takes_damage: bool) {
-        draw_texture(get_texture(0), self.coords.x + GAME_SCREEN_MAIN.x, self.coords.y + GAME_SCREEN_MAIN.y, if takes_damage { RED } else { WHITE });
-    }
-}
 
-/// get tile at the coords `coords` and move to `direction` if it isn't diagonal. If the tile collides, return `true`, else `false`
-fn get_tile_collisionmap(coords: &Vec2, direction_from_coords: &Direction, tilemap: &Vec<CollisionType>, speed: f32) -> bool {
-    for tile in tilemap {
-        if let CollisionType::Solid(x, y) = tile {
-            let offset_coords = *coords + {
-                // offset
-                if *direction_from_coords == Direction::Up {
-                    vec2(0., -speed)
-                } else if *direction_from_coords == Direction::Right {
-                    vec2(speed, 0.)
-                } else if *direction_from_coords == Direction::Left {
-                    vec2(-speed, 0.)
-                } else if *direction_from_coords == Direction::Down {
-                    vec2(0., speed)
-                } else {
-                    vec2(0., 0.)
-                }
-            };
-            if (offset_coords.x / TILE_SIZE) as u32 == *x && (offset_coords.y / TILE_SIZE) as u32 == *y {
-                return true;
+    /// whether the enemy overlaps a wall
+    pub fn collides(&self, collision_map: &[CollisionType]) -> bool {
+        let hitbox = Rect::new(self.coords.x + 1., self.coords.y + 1., self.wh.x - 3., self.wh.y - 3.);
+        collision_map.iter().any(|tile| {
+            if let CollisionType::Solid(x, y) = tile {
+                hitbox.overlaps(&Rect::new(*x as f32 * TILE_SIZE, *y as f32 * TILE_SIZE, TILE_SIZE, TILE_SIZE))
+            } else {
+                false
             }
-        }
+        })
+    }
+    /// draw the enemy with the texture of its kind. If `takes_damage` is true, show damage animation
+    pub fn draw(&self, texture: &Texture2D, takes_damage: bool) {
+        draw_texture(texture, self.coords.x + GAME_SCREEN_MAIN.x, self.coords.y + GAME_SCREEN_MAIN.y, if takes_damage { RED } else { WHITE });
     }
-    false
 }
-
-
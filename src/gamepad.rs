@@ -0,0 +1,198 @@
+use macroquad::prelude::*;
+use crate::input::Action;
+
+/// sticks closer to the centre than this count as centred
+#[cfg(gamepad)]
+const DEADZONE: f32 = 0.25;
+
+/// the buttons the game uses, named after their position like gilrs does
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PadButton {
+    /// A on Xbox pads, cross on PlayStation pads
+    South,
+    East,
+    North,
+    Start,
+    Select,
+    LeftTrigger,
+    RightTrigger,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl PadButton {
+    #[cfg(gamepad)]
+    fn to_gilrs(self) -> gilrs::Button {
+        match self {
+            PadButton::South => gilrs::Button::South,
+            PadButton::East => gilrs::Button::East,
+            PadButton::North => gilrs::Button::North,
+            PadButton::Start => gilrs::Button::Start,
+            PadButton::Select => gilrs::Button::Select,
+            PadButton::LeftTrigger => gilrs::Button::LeftTrigger,
+            PadButton::RightTrigger => gilrs::Button::RightTrigger,
+            PadButton::DPadUp => gilrs::Button::DPadUp,
+            PadButton::DPadDown => gilrs::Button::DPadDown,
+            PadButton::DPadLeft => gilrs::Button::DPadLeft,
+            PadButton::DPadRight => gilrs::Button::DPadRight,
+        }
+    }
+
+    #[cfg(gamepad)]
+    fn from_gilrs(button: gilrs::Button) -> Option<Self> {
+        match button {
+            gilrs::Button::South => Some(PadButton::South),
+            gilrs::Button::East => Some(PadButton::East),
+            gilrs::Button::North => Some(PadButton::North),
+            gilrs::Button::Start => Some(PadButton::Start),
+            gilrs::Button::Select => Some(PadButton::Select),
+            gilrs::Button::LeftTrigger => Some(PadButton::LeftTrigger),
+            gilrs::Button::RightTrigger => Some(PadButton::RightTrigger),
+            gilrs::Button::DPadUp => Some(PadButton::DPadUp),
+            gilrs::Button::DPadDown => Some(PadButton::DPadDown),
+            gilrs::Button::DPadLeft => Some(PadButton::DPadLeft),
+            gilrs::Button::DPadRight => Some(PadButton::DPadRight),
+            _ => None,
+        }
+    }
+}
+
+/// the buttons of `action` on a gamepad. The sticks move and aim, see `InputFrame::poll`
+pub fn pad_buttons(action: Action) -> &'static [PadButton] {
+    match action {
+        Action::MoveUp | Action::MenuUp => &[PadButton::DPadUp],
+        Action::MoveDown | Action::MenuDown => &[PadButton::DPadDown],
+        Action::MoveLeft | Action::MenuLeft => &[PadButton::DPadLeft],
+        Action::MoveRight | Action::MenuRight => &[PadButton::DPadRight],
+        Action::ShootUp | Action::ShootDown | Action::ShootLeft | Action::ShootRight => &[],
+        Action::UseItem => &[PadButton::South, PadButton::LeftTrigger, PadButton::RightTrigger],
+        Action::Pause => &[PadButton::Start],
+        Action::Confirm => &[PadButton::South, PadButton::Start],
+        Action::Back => &[PadButton::East],
+        Action::OpenSettings => &[PadButton::North],
+        Action::SaveAndQuit => &[PadButton::Select],
+    }
+}
+
+/// the left or the right analog stick
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+/// the connected gamepads. The one that was used last is the one that plays, so pads can be
+/// plugged in, swapped and pulled out at any time
+pub struct Gamepads {
+    #[cfg(gamepad)]
+    gilrs: Option<gilrs::Gilrs>,
+    #[cfg(gamepad)]
+    active: Option<gilrs::GamepadId>,
+    /// buttons of the active pad pressed since the last `update`
+    pressed: Vec<PadButton>,
+}
+
+impl Gamepads {
+    /// start listening for gamepads. Without the `gamepad` feature, or if the platform has no
+    /// gamepad support, there just never are any
+    pub fn new() -> Self {
+        #[cfg(gamepad)]
+        {
+            let gilrs = match gilrs::Gilrs::new() {
+                Ok(gilrs) => Some(gilrs),
+                Err(err) => {
+                    println!("WARN: no gamepad support: {}", err);
+                    None
+                }
+            };
+            let active = gilrs.as_ref().and_then(|gilrs| gilrs.gamepads().next().map(|(id, _)| id));
+            Self { gilrs, active, pressed: vec![] }
+        }
+        #[cfg(not(gamepad))]
+        Self { pressed: vec![] }
+    }
+
+    /// handle the gamepad events since the last frame. Call once per frame
+    pub fn update(&mut self) {
+        self.pressed.clear();
+        #[cfg(gamepad)]
+        {
+            let Some(gilrs) = &mut self.gilrs else {
+                return;
+            };
+            while let Some(event) = gilrs.next_event() {
+                match event.event {
+                    gilrs::EventType::Connected => {
+                        println!("[INFO] Gamepad connected: {}", gilrs.gamepad(event.id).name());
+                        if self.active.is_none() {
+                            self.active = Some(event.id);
+                        }
+                    }
+                    gilrs::EventType::Disconnected => {
+                        println!("[INFO] Gamepad disconnected: {}", gilrs.gamepad(event.id).name());
+                        if self.active == Some(event.id) {
+                            self.active = gilrs.gamepads().map(|(id, _)| id).find(|id| *id != event.id);
+                        }
+                    }
+                    gilrs::EventType::ButtonPressed(button, _) => {
+                        self.active = Some(event.id);
+                        if let Some(button) = PadButton::from_gilrs(button) {
+                            self.pressed.push(button);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// whether `button` of the active pad is held
+    pub fn down(&self, button: PadButton) -> bool {
+        #[cfg(gamepad)]
+        if let (Some(gilrs), Some(id)) = (&self.gilrs, self.active) {
+            return gilrs.gamepad(id).is_pressed(button.to_gilrs());
+        }
+        #[cfg(not(gamepad))]
+        let _ = button;
+        false
+    }
+
+    /// whether `button` of the active pad was pressed this frame
+    pub fn pressed(&self, button: PadButton) -> bool {
+        self.pressed.contains(&button)
+    }
+
+    /// whether a button of `action` is held
+    pub fn action_down(&self, action: Action) -> bool {
+        pad_buttons(action).iter().any(|button| self.down(*button))
+    }
+
+    /// whether a button of `action` was pressed this frame
+    pub fn action_pressed(&self, action: Action) -> bool {
+        pad_buttons(action).iter().any(|button| self.pressed(*button))
+    }
+
+    /// where `stick` of the active pad points, with y pointing down like on the screen.
+    /// Zero inside the deadzone, at most 1 long
+    pub fn stick(&self, stick: Stick) -> Vec2 {
+        #[cfg(gamepad)]
+        if let (Some(gilrs), Some(id)) = (&self.gilrs, self.active) {
+            let gamepad = gilrs.gamepad(id);
+            let (x, y) = match stick {
+                Stick::Left => (gilrs::Axis::LeftStickX, gilrs::Axis::LeftStickY),
+                Stick::Right => (gilrs::Axis::RightStickX, gilrs::Axis::RightStickY),
+            };
+            // gilrs' y axis points up
+            let value = vec2(gamepad.value(x), -gamepad.value(y));
+            if value.length() < DEADZONE {
+                return Vec2::ZERO;
+            }
+            return value.clamp_length_max(1.);
+        }
+        #[cfg(not(gamepad))]
+        let _ = stick;
+        Vec2::ZERO
+    }
+}
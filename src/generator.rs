@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+
+// random rooms in the layout of the hand-made maps: the walls of `map.json` with a door in the
+// middle of every edge, obstacles, hazards and spawnpoints inside, written out as Tiled JSON.
+// The same seed always gives the same room
+
+/// size of a room in tiles
+pub const ROOM_SIZE: usize = 16;
+/// the tile the player starts on
+const CENTRE: (usize, usize) = (ROOM_SIZE / 2, ROOM_SIZE / 2);
+
+// gids of the tiles of `tilemap.png`
+const FLOOR: u32 = 49;
+const FLOOR_CRACKED: u32 = 50;
+const PATH: u32 = 43;
+const SAND: u32 = 52;
+const SPIKES: u32 = 42;
+const CHEST: u32 = 90;
+const PILLAR_TOP: u32 = 64;
+const PILLAR_BOTTOM: u32 = 76;
+const SPAWNPOINT: u32 = 121;
+
+/// the `main` layer of `map.json`
+const WALLS: [u32; ROOM_SIZE * ROOM_SIZE] = [
+     2,  3,  7,  3,  3,  3, 18,  0,  0, 17,  3,  3,  3,  7,  3,  4,
+    14, 15, 19, 15, 15, 15, 15,  0,  0, 15, 15, 15, 15, 19, 15, 16,
+    14,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, 16,
+    14,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, 16,
+    14,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, 16,
+    18,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, 17,
+    15,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, 15,
+     0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,
+     6,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  5,
+    14,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, 16,
+    14,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, 16,
+    14,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, 16,
+    14,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, 16,
+    14,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, 16,
+    26, 27, 27, 27, 27, 27,  6,  0,  0,  5, 27, 27, 27, 27, 27, 28,
+];
+/// decorations on the walls, from the `objects` layer of `map.json`
+const DECORATIONS: [(usize, usize, u32); 6] = [(5, 1, 20), (6, 1, 30), (9, 1, 30), (10, 1, 20), (0, 6, 29), (15, 6, 29)];
+
+/// the tileset of the maps, with the tile properties of spikes, sand and chests
+const TILESET: &str = r#"{
+         "columns":12,
+         "firstgid":1,
+         "image":"tilemap.png",
+         "imageheight":176,
+         "imagewidth":192,
+         "margin":0,
+         "name":"tilemap",
+         "spacing":0,
+         "tilecount":132,
+         "tileheight":16,
+         "tiles":[
+                {"id":41, "properties":[{"name":"blocks_bullets", "type":"bool", "value":false}, {"name":"blocks_walk", "type":"bool", "value":false}, {"name":"damage", "type":"bool", "value":true}]},
+                {"id":51, "properties":[{"name":"blocks_bullets", "type":"bool", "value":false}, {"name":"blocks_walk", "type":"bool", "value":false}, {"name":"slow", "type":"float", "value":0.5}]},
+                {"id":89, "properties":[{"name":"destructible", "type":"int", "value":3}]}
+         ],
+         "tilewidth":16
+        }"#;
+
+/// name of the room generated from `seed`, for the stage list and the exported map
+pub fn map_path(seed: u64) -> String {
+    format!("generated-{}.json", seed)
+}
+
+/// small xorshift generator, so rooms don't depend on (or disturb) macroquad's global one
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // splitmix the seed, so seeds next to each other give different rooms and 0 works
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Self((z ^ (z >> 31)) | 1)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    /// a number from `low` to `high`, `high` excluded
+    pub fn range(&mut self, low: usize, high: usize) -> usize {
+        low + (self.next_u64() % (high - low) as u64) as usize
+    }
+}
+
+/// the tile layers of a room, `ROOM_SIZE` * `ROOM_SIZE` gids each
+pub struct Room {
+    pub background: Vec<u32>,
+    pub main: Vec<u32>,
+    pub objects: Vec<u32>,
+    pub spawnable: Vec<u32>,
+}
+
+fn index(x: usize, y: usize) -> usize {
+    y * ROOM_SIZE + x
+}
+
+impl Room {
+    /// whether the player can walk over the tile without getting hurt: no wall, obstacle or spikes
+    fn walkable(&self, x: usize, y: usize) -> bool {
+        self.main[index(x, y)] == 0
+    }
+
+    /// every walkable tile can be reached from the centre, so the doors, the spawnpoints and
+    /// every bit of floor are connected to where the player starts
+    fn connected(&self) -> bool {
+        let mut reachable = vec![false; ROOM_SIZE * ROOM_SIZE];
+        let mut queue = VecDeque::new();
+        reachable[index(CENTRE.0, CENTRE.1)] = true;
+        queue.push_back(CENTRE);
+        while let Some((x, y)) = queue.pop_front() {
+            for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if nx < 0 || ny < 0 || nx >= ROOM_SIZE as i32 || ny >= ROOM_SIZE as i32 {
+                    continue;
+                }
+                let (nx, ny) = (nx as usize, ny as usize);
+                if !reachable[index(nx, ny)] && self.walkable(nx, ny) {
+                    reachable[index(nx, ny)] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        (0..ROOM_SIZE * ROOM_SIZE).all(|i| reachable[i] || !self.walkable(i % ROOM_SIZE, i / ROOM_SIZE))
+    }
+
+    /// the room as a Tiled JSON map, like the ones in `assets`
+    pub fn to_tiled_json(&self) -> String {
+        let layer = |id: u32, name: &str, data: &[u32]| {
+            let rows: Vec<String> = data.chunks(ROOM_SIZE).map(|row| row.iter().map(|gid| gid.to_string()).collect::<Vec<String>>().join(", ")).collect();
+            format!(
+                "        {{\n         \"data\":[{}],\n         \"height\":{},\n         \"id\":{},\n         \"name\":\"{}\",\n         \"opacity\":1,\n         \"type\":\"tilelayer\",\n         \"visible\":true,\n         \"width\":{},\n         \"x\":0,\n         \"y\":0\n        }}",
+                rows.join(",\n            "), ROOM_SIZE, id, name, ROOM_SIZE
+            )
+        };
+        let layers = [
+            layer(3, "background", &self.background),
+            layer(1, "main", &self.main),
+            layer(2, "objects", &self.objects),
+            layer(5, "spawnable", &self.spawnable),
+        ];
+        format!(
+            "{{ \"compressionlevel\":-1,\n \"height\":{},\n \"infinite\":false,\n \"layers\":[\n{}],\n \"nextlayerid\":6,\n \"nextobjectid\":1,\n \"orientation\":\"orthogonal\",\n \"renderorder\":\"right-down\",\n \"tiledversion\":\"1.11.0\",\n \"tileheight\":16,\n \"tilesets\":[\n        {}],\n \"tilewidth\":16,\n \"type\":\"map\",\n \"version\":\"1.10\",\n \"width\":{}\n}}\n",
+            ROOM_SIZE, layers.join(",\n"), TILESET, ROOM_SIZE
+        )
+    }
+}
+
+/// a random room. Every walkable tile, including the doors and the spawnpoints, can be reached from the centre
+pub fn generate(seed: u64) -> Room {
+    let mut rng = Rng::new(seed);
+    let mut room = Room {
+        background: vec![FLOOR; ROOM_SIZE * ROOM_SIZE],
+        main: WALLS.to_vec(),
+        objects: vec![0; ROOM_SIZE * ROOM_SIZE],
+        spawnable: vec![0; ROOM_SIZE * ROOM_SIZE],
+    };
+    for (x, y, gid) in DECORATIONS {
+        room.objects[index(x, y)] = gid;
+    }
+
+    // floor: a path between the doors, cracks and patches of sand
+    for i in 0..ROOM_SIZE {
+        for j in [7, 8] {
+            room.background[index(i, j)] = PATH;
+            room.background[index(j, i)] = PATH;
+        }
+    }
+    for _ in 0..rng.range(4, 12) {
+        let (x, y) = (rng.range(1, ROOM_SIZE - 1), rng.range(2, ROOM_SIZE - 1));
+        room.background[index(x, y)] = FLOOR_CRACKED;
+    }
+    for _ in 0..rng.range(0, 3) {
+        let (w, h) = (rng.range(2, 4), rng.range(2, 4));
+        let (x, y) = (rng.range(1, ROOM_SIZE - 1 - w), rng.range(2, ROOM_SIZE - 1 - h));
+        for sand_y in y..y + h {
+            for sand_x in x..x + w {
+                room.background[index(sand_x, sand_y)] = SAND;
+            }
+        }
+    }
+
+    // obstacles, each one only if the room stays connected. Spikes are walkable but hurt, so paths don't count them
+    for _ in 0..rng.range(6, 16) {
+        let (x, y) = (rng.range(2, ROOM_SIZE - 2), rng.range(3, ROOM_SIZE - 2));
+        // keep the centre free for the player
+        if x.abs_diff(CENTRE.0) <= 2 && y.abs_diff(CENTRE.1) <= 2 {
+            continue;
+        }
+        let tiles = match rng.range(0, 3) {
+            0 => vec![(x, y, PILLAR_TOP), (x, y + 1, PILLAR_BOTTOM)],
+            1 => vec![(x, y, CHEST)],
+            _ => vec![(x, y, SPIKES)],
+        };
+        if tiles.iter().any(|(x, y, _)| *y >= ROOM_SIZE - 1 || room.main[index(*x, *y)] != 0) {
+            continue;
+        }
+        for (x, y, gid) in &tiles {
+            room.main[index(*x, *y)] = *gid;
+        }
+        if !room.connected() {
+            for (x, y, _) in &tiles {
+                room.main[index(*x, *y)] = 0;
+            }
+        }
+    }
+
+    // spawnpoints on free floor, away from the player
+    let mut free: Vec<(usize, usize)> = (2..ROOM_SIZE - 1)
+        .flat_map(|y| (1..ROOM_SIZE - 1).map(move |x| (x, y)))
+        .filter(|(x, y)| room.walkable(*x, *y) && x.abs_diff(CENTRE.0) + y.abs_diff(CENTRE.1) >= 6)
+        .collect();
+    for _ in 0..rng.range(4, 9).min(free.len()) {
+        let (x, y) = free.swap_remove(rng.range(0, free.len()));
+        room.spawnable[index(x, y)] = SPAWNPOINT;
+    }
+    room
+}
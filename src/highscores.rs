@@ -0,0 +1,81 @@
+use nanoserde::{DeJson, SerJson};
+use crate::storage::{read_data, write_data};
+use crate::world::World;
+
+/// file in the data dir the high scores are kept in
+pub const HIGHSCORES_FILE: &str = "highscores.json";
+/// entries the table keeps
+pub const MAX_ENTRIES: usize = 10;
+/// longest name that can be entered
+pub const MAX_NAME_LEN: usize = 12;
+
+/// one run in the high score table
+#[derive(DeJson, SerJson, Clone, Debug)]
+pub struct HighScore {
+    pub name: String,
+    pub score: i32,
+    pub kills: i32,
+    /// `campaign` or `endless`
+    pub mode: String,
+    /// rooms cleared, or waves survived in endless runs
+    pub stages: i32,
+    /// whether the campaign was won
+    #[nserde(default)]
+    pub won: bool,
+}
+
+impl HighScore {
+    /// the entry for the run of `world`, without a name yet
+    pub fn from_world(world: &World, won: bool) -> Self {
+        Self {
+            name: String::new(),
+            score: world.score,
+            kills: world.enemies_killcount,
+            mode: if world.endless.is_some() { "endless" } else { "campaign" }.to_string(),
+            stages: world.stages_cleared,
+            won,
+        }
+    }
+}
+
+/// the best runs, best first
+#[derive(DeJson, SerJson, Default)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// read the table from the data dir. Empty if there isn't one yet or it's broken
+    pub fn load() -> Self {
+        let Some(json) = read_data(HIGHSCORES_FILE) else {
+            return Self::default();
+        };
+        match HighScores::deserialize_json(&json) {
+            Ok(highscores) => highscores,
+            Err(err) => {
+                println!("WARN: couldn't parse {}: {}. Starting a new high score table", HIGHSCORES_FILE, err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        write_data(HIGHSCORES_FILE, &self.serialize_json());
+    }
+
+    /// whether a run with `score` gets into the table
+    pub fn qualifies(&self, score: i32) -> bool {
+        score > 0 && (self.entries.len() < MAX_ENTRIES || self.entries.iter().any(|entry| entry.score < score))
+    }
+
+    /// put `entry` into the table, dropping the worst one if it's full. Returns its place, counting from 0
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let place = self.entries.iter().position(|other| other.score < entry.score).unwrap_or(self.entries.len());
+        if place >= MAX_ENTRIES {
+            return None;
+        }
+        self.entries.insert(place, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(place)
+    }
+}
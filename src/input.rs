@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+use macroquad::prelude::*;
+use nanoserde::{DeJson, SerJson};
+use crate::bullet::Direction;
+use crate::gamepad::{Gamepads, Stick};
+use crate::settings::Settings;
+
+/// keys that can be bound to actions. Escape is only there for the default bindings, it cancels
+/// rebinding and can't be bound anew. Backspace removes bindings, F3 and F4 are taken by the debug tools
+const BINDABLE_KEYS: [KeyCode; 72] = [
+    KeyCode::Escape,
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Kp0, KeyCode::Kp1, KeyCode::Kp2, KeyCode::Kp3, KeyCode::Kp4,
+    KeyCode::Kp5, KeyCode::Kp6, KeyCode::Kp7, KeyCode::Kp8, KeyCode::Kp9,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Space, KeyCode::Enter, KeyCode::Tab,
+    KeyCode::LeftShift, KeyCode::RightShift, KeyCode::LeftControl, KeyCode::RightControl, KeyCode::LeftAlt, KeyCode::RightAlt,
+    KeyCode::Comma, KeyCode::Period, KeyCode::Slash, KeyCode::Semicolon, KeyCode::Apostrophe, KeyCode::Minus,
+    KeyCode::Equal, KeyCode::LeftBracket, KeyCode::RightBracket, KeyCode::Backslash, KeyCode::GraveAccent, KeyCode::Insert,
+];
+
+/// name of a key in the settings file, e.g. `W`, `Up` or `Space`
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+/// whether `key` can be bound on the controls screen
+pub fn is_bindable(key: KeyCode) -> bool {
+    key != KeyCode::Escape && BINDABLE_KEYS.contains(&key)
+}
+
+/// the bindable key called `name`
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    BINDABLE_KEYS.into_iter().find(|key| key_name(*key) == name)
+}
+
+/// something the player can do, bound to one or more keys
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    ShootUp,
+    ShootDown,
+    ShootLeft,
+    ShootRight,
+    UseItem,
+    Pause,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    Confirm,
+    Back,
+    OpenSettings,
+    SaveAndQuit,
+}
+
+impl Action {
+    pub const ALL: [Action; 18] = [
+        Action::MoveUp, Action::MoveDown, Action::MoveLeft, Action::MoveRight,
+        Action::ShootUp, Action::ShootDown, Action::ShootLeft, Action::ShootRight,
+        Action::UseItem, Action::Pause,
+        Action::MenuUp, Action::MenuDown, Action::MenuLeft, Action::MenuRight,
+        Action::Confirm, Action::Back, Action::OpenSettings, Action::SaveAndQuit,
+    ];
+
+    /// the action's entry in the registry
+    pub fn info(self) -> &'static ActionInfo {
+        &ACTIONS[self as usize]
+    }
+
+    /// name of the action in the settings file, e.g. `move_up`
+    pub fn name(self) -> &'static str {
+        self.info().name
+    }
+
+    /// name on the controls screen
+    pub fn label(self) -> &'static str {
+        self.info().label
+    }
+
+    /// whether the action is used in the menus rather than while playing. A key can only be
+    /// bound to one action of each group
+    pub fn is_menu(self) -> bool {
+        self.info().menu
+    }
+
+    pub fn default_keys(self) -> Vec<KeyCode> {
+        self.info().default_keys.to_vec()
+    }
+}
+
+/// names, group and default keys of one action
+pub struct ActionInfo {
+    /// name in the settings file
+    pub name: &'static str,
+    /// name on the controls screen
+    pub label: &'static str,
+    pub menu: bool,
+    pub default_keys: &'static [KeyCode],
+}
+
+/// the action registry. The order has to match `Action`
+const ACTIONS: [ActionInfo; 18] = [
+    ActionInfo { name: "move_up", label: "Move Up", menu: false, default_keys: &[KeyCode::W] },
+    ActionInfo { name: "move_down", label: "Move Down", menu: false, default_keys: &[KeyCode::S] },
+    ActionInfo { name: "move_left", label: "Move Left", menu: false, default_keys: &[KeyCode::A] },
+    ActionInfo { name: "move_right", label: "Move Right", menu: false, default_keys: &[KeyCode::D] },
+    ActionInfo { name: "shoot_up", label: "Shoot Up", menu: false, default_keys: &[KeyCode::Up] },
+    ActionInfo { name: "shoot_down", label: "Shoot Down", menu: false, default_keys: &[KeyCode::Down] },
+    ActionInfo { name: "shoot_left", label: "Shoot Left", menu: false, default_keys: &[KeyCode::Left] },
+    ActionInfo { name: "shoot_right", label: "Shoot Right", menu: false, default_keys: &[KeyCode::Right] },
+    ActionInfo { name: "use_item", label: "Use Item", menu: false, default_keys: &[KeyCode::Space] },
+    ActionInfo { name: "pause", label: "Pause", menu: false, default_keys: &[KeyCode::Escape, KeyCode::P] },
+    ActionInfo { name: "menu_up", label: "Menu Up", menu: true, default_keys: &[KeyCode::Up] },
+    ActionInfo { name: "menu_down", label: "Menu Down", menu: true, default_keys: &[KeyCode::Down] },
+    ActionInfo { name: "menu_left", label: "Menu Left", menu: true, default_keys: &[KeyCode::Left] },
+    ActionInfo { name: "menu_right", label: "Menu Right", menu: true, default_keys: &[KeyCode::Right] },
+    ActionInfo { name: "confirm", label: "Confirm", menu: true, default_keys: &[KeyCode::Enter, KeyCode::Space] },
+    ActionInfo { name: "back", label: "Back", menu: true, default_keys: &[KeyCode::Escape] },
+    ActionInfo { name: "open_settings", label: "Settings (Pause Menu)", menu: true, default_keys: &[KeyCode::S] },
+    ActionInfo { name: "save_and_quit", label: "Save & Quit (Pause Menu)", menu: true, default_keys: &[KeyCode::Q] },
+];
+
+/// keys an action can have at once. Binding another one replaces the oldest
+pub const MAX_BINDINGS: usize = 3;
+
+/// the key names of every action, by action name (see `Action::name`). Missing actions, empty
+/// lists or unknown names mean the default keys
+#[derive(DeJson, SerJson, Clone, Debug)]
+#[nserde(transparent)]
+pub struct Keybindings(HashMap<String, Vec<String>>);
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        let mut keys = Self(HashMap::new());
+        keys.reset();
+        keys
+    }
+}
+
+impl Keybindings {
+    fn set(&mut self, action: Action, keys: Vec<KeyCode>) {
+        self.0.insert(action.name().to_string(), keys.into_iter().map(key_name).collect());
+    }
+
+    /// the keys bound to `action`, the default ones if none of its names are known
+    pub fn keys(&self, action: Action) -> Vec<KeyCode> {
+        let keys: Vec<KeyCode> = self.0.get(action.name()).into_iter().flatten().filter_map(|name| key_from_name(name)).collect();
+        if keys.is_empty() { action.default_keys() } else { keys }
+    }
+
+    /// whether a key of `action` is held
+    pub fn down(&self, action: Action) -> bool {
+        self.keys(action).into_iter().any(is_key_down)
+    }
+
+    /// whether a key of `action` was pressed this frame
+    pub fn pressed(&self, action: Action) -> bool {
+        self.keys(action).into_iter().any(is_key_pressed)
+    }
+
+    /// the first key of `action`, for hints like "[Q] Save & Quit"
+    pub fn hint(&self, action: Action) -> String {
+        key_name(self.keys(action)[0])
+    }
+
+    /// every key of `action`, e.g. "Enter, Space"
+    pub fn describe(&self, action: Action) -> String {
+        self.keys(action).into_iter().map(key_name).collect::<Vec<String>>().join(", ")
+    }
+
+    /// add `key` to the keys of `action`. If another action of the same group has it already,
+    /// nothing changes and that action is returned
+    pub fn bind(&mut self, action: Action, key: KeyCode) -> Result<(), Action> {
+        if let Some(other) = Action::ALL.into_iter().find(|other| *other != action && other.is_menu() == action.is_menu() && self.keys(*other).contains(&key)) {
+            return Err(other);
+        }
+        let mut keys = self.keys(action);
+        if keys.contains(&key) {
+            return Ok(());
+        }
+        if keys.len() >= MAX_BINDINGS {
+            keys.remove(0);
+        }
+        keys.push(key);
+        self.set(action, keys);
+        Ok(())
+    }
+
+    /// remove the newest key of `action`. Its only key stays, so nothing becomes unreachable
+    pub fn unbind_last(&mut self, action: Action) {
+        let mut keys = self.keys(action);
+        if keys.len() > 1 {
+            keys.pop();
+            self.set(action, keys);
+        }
+    }
+
+    /// bind every action to its default keys
+    pub fn reset(&mut self) {
+        for action in Action::ALL {
+            self.set(action, action.default_keys());
+        }
+    }
+}
+
+/// the player's input for one fixed tick
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct InputFrame {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub shoot_up: bool,
+    pub shoot_down: bool,
+    pub shoot_left: bool,
+    pub shoot_right: bool,
+    /// the item key was pressed this frame, so one press uses one item
+    pub use_item: bool,
+    /// movement from an analog stick, at most 1 long. Zero if it's centred
+    pub movement: Vec2,
+    /// direction to shoot in from an analog stick or the mouse, at any angle. Zero if there's
+    /// none or aim is snapped to eight directions
+    pub aim: Vec2,
+}
+
+impl InputFrame {
+    /// read the keyboard through the player's bindings and the active gamepad: the left stick
+    /// moves, the right stick aims and shoots. Without the stick, `mouse` (from the player to the
+    /// cursor, while the mouse button is held) does. Both at any angle or snapped to eight directions
+    pub fn poll(settings: &Settings, gamepads: &Gamepads, mouse: Option<Vec2>) -> Self {
+        let keys = &settings.keybindings;
+        let mut input = Self {
+            up: keys.down(Action::MoveUp),
+            down: keys.down(Action::MoveDown),
+            left: keys.down(Action::MoveLeft),
+            right: keys.down(Action::MoveRight),
+            shoot_up: keys.down(Action::ShootUp),
+            shoot_down: keys.down(Action::ShootDown),
+            shoot_left: keys.down(Action::ShootLeft),
+            shoot_right: keys.down(Action::ShootRight),
+            use_item: keys.pressed(Action::UseItem) || gamepads.action_pressed(Action::UseItem),
+            movement: gamepads.stick(Stick::Left),
+            aim: Vec2::ZERO,
+        };
+        input.up |= gamepads.action_down(Action::MoveUp);
+        input.down |= gamepads.action_down(Action::MoveDown);
+        input.left |= gamepads.action_down(Action::MoveLeft);
+        input.right |= gamepads.action_down(Action::MoveRight);
+
+        let mut aim = gamepads.stick(Stick::Right);
+        if aim == Vec2::ZERO {
+            aim = mouse.unwrap_or(Vec2::ZERO);
+        }
+        if aim == Vec2::ZERO {
+            return input;
+        }
+        if settings.analog_aim {
+            input.aim = aim;
+        } else {
+            let direction = Direction::from_vector(aim);
+            input.shoot_up |= matches!(direction, Direction::Up | Direction::Leftup | Direction::Rightup);
+            input.shoot_down |= matches!(direction, Direction::Down | Direction::Leftdown | Direction::Rightdown);
+            input.shoot_left |= matches!(direction, Direction::Left | Direction::Leftup | Direction::Leftdown);
+            input.shoot_right |= matches!(direction, Direction::Right | Direction::Rightup | Direction::Rightdown);
+        }
+        input
+    }
+}
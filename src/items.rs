@@ -1,11 +1,163 @@
 use macroquad::prelude::*;
-#[derive(Clone, Copy, PartialEq)]
+use macroquad::rand::gen_range;
+use crate::{GAME_SCREEN_MAIN, SPEED, TILE_SIZE};
+use crate::collision::CollisionType;
+use crate::player::Player;
+use crate::world::World;
+
+/// 1 in `ITEM_DROP_CHANCE` killed enemies drops an item
+pub const ITEM_DROP_CHANCE: i32 = 10;
+/// ticks a dropped item stays on the map
+pub const ITEM_LIFETIME: i32 = 600;
+/// ticks before despawning in which the item blinks
+pub const ITEM_BLINK_TIME: i32 = 180;
+
+/// the order has to match `ITEMS`
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Item {
     Speed,
     Quickshoot,
+    SpreadShot,
+    MachineGun,
+    Piercing,
+    Nuke,
+    ExtraLife,
+    SmokeBomb,
+    Badge,
+    Bouncing,
+}
+
+impl Item {
+    pub const ALL: [Item; 10] = [Item::Speed, Item::Quickshoot, Item::SpreadShot, Item::MachineGun, Item::Piercing, Item::Nuke, Item::ExtraLife, Item::SmokeBomb, Item::Badge, Item::Bouncing];
+
+    pub fn random() -> Self {
+        Item::ALL[gen_range(0, Item::ALL.len())]
+    }
+    /// the item whose texture key without `item::` is `key`, e.g. `coffee`
+    pub fn from_key(key: &str) -> Option<Self> {
+        Item::ALL.into_iter().find(|item| item.key() == key)
+    }
+    /// name of the item in maps and save files: its texture key without `item::`
+    pub fn key(&self) -> &'static str {
+        self.texture_name().trim_start_matches("item::")
+    }
+    /// the item's entry in the registry
+    pub fn info(&self) -> &'static ItemInfo {
+        ITEMS.get(*self)
+    }
+    /// ticks the item's effect lasts once activated. 0 for items that only do something when used
+    pub fn duration(&self) -> i32 {
+        self.info().duration
+    }
+    /// key of the item's texture in `GameState::assets`
+    pub fn texture_name(&self) -> &'static str {
+        self.info().texture
+    }
+}
+
+/// name, texture and behaviour of one item
+pub struct ItemInfo {
+    pub name: &'static str,
+    /// key of the texture in `GameState::assets`
+    pub texture: &'static str,
+    pub duration: i32,
+    /// called once when the player uses the item
+    pub on_use: fn(&mut World),
+    /// called every tick by `player::update_fixed` while the effect is active
+    pub on_tick: fn(&mut Player),
+}
+
+/// the item registry
+pub struct Items {
+    infos: [ItemInfo; 10],
+}
+
+impl Items {
+    pub fn get(&self, item: Item) -> &ItemInfo {
+        &self.infos[item as usize]
+    }
+}
+
+pub static ITEMS: Items = Items {
+    infos: [
+        ItemInfo { name: "Coffee", texture: "item::coffee", duration: 900, on_use: no_use, on_tick: |player| player.speed = SPEED * 1.5 },
+        ItemInfo { name: "Quickshoot", texture: "item::quickshoot", duration: 600, on_use: no_use, on_tick: |player| player.quickshoot = 12 },
+        ItemInfo { name: "Spread Shot", texture: "item::spread", duration: 600, on_use: no_use, on_tick: |player| player.spread = true },
+        ItemInfo { name: "Machine Gun", texture: "item::machinegun", duration: 480, on_use: no_use, on_tick: |player| player.quickshoot = 24 },
+        ItemInfo { name: "Piercing Bullets", texture: "item::piercing", duration: 600, on_use: no_use, on_tick: |player| player.piercing = true },
+        ItemInfo { name: "Nuke", texture: "item::nuke", duration: 0, on_use: nuke, on_tick: no_tick },
+        ItemInfo { name: "Extra Life", texture: "item::extra_life", duration: 0, on_use: |world| world.player.health += 1, on_tick: no_tick },
+        ItemInfo { name: "Smoke Bomb", texture: "item::smoke_bomb", duration: 300, on_use: smoke_bomb, on_tick: no_tick },
+        ItemInfo { name: "Sheriff Badge", texture: "item::badge", duration: 480, on_use: no_use, on_tick: |player| player.invincible = true },
+        ItemInfo { name: "Bouncy Bullets", texture: "item::bouncing", duration: 600, on_use: no_use, on_tick: |player| player.bouncing = true },
+    ],
+};
+
+fn no_use(_world: &mut World) {}
+fn no_tick(_player: &mut Player) {}
+
+/// kill every enemy on screen
+fn nuke(world: &mut World) {
+    for enemy in std::mem::take(&mut world.enemies) {
+        world.on_enemy_killed(&enemy);
+    }
 }
 
-struct Items {
-    name: String,
-    texture: Texture2D,
-}
\ No newline at end of file
+/// teleport the player to a random safe tile they could walk to. Enemies keep chasing the spot the player left until the effect ends
+fn smoke_bomb(world: &mut World) {
+    // walk the flow field from the player, so the player can't end up enclosed by walls
+    world.flow_field.update(world.player.coords + world.player.wh / 2.);
+    let free_tiles: Vec<Vec2> = world.collision_map.iter().filter_map(|tile| {
+        let CollisionType::Empty(x, y) = *tile else {
+            return None;
+        };
+        let coords = vec2(x as f32 * TILE_SIZE, y as f32 * TILE_SIZE);
+        if world.terrain.tile(x, y).damage || !world.flow_field.reaches(coords + TILE_SIZE / 2.) {
+            return None;
+        }
+        Some(coords)
+    }).collect();
+    if free_tiles.is_empty() {
+        return;
+    }
+    world.player.decoy = Some(world.player.coords);
+    world.player.coords = free_tiles[gen_range(0, free_tiles.len())];
+}
+
+/// an item lying on the map, waiting to be picked up
+#[derive(Clone)]
+pub struct ItemDrop {
+    pub item: Item,
+    pub coords: Vec2,
+    pub wh: Vec2,
+    /// ticks until the item despawns
+    pub lifetime: i32,
+}
+
+impl ItemDrop {
+    pub fn new(item: Item, coords: Vec2) -> Self {
+        Self {
+            item,
+            coords,
+            wh: vec2(TILE_SIZE, TILE_SIZE),
+            lifetime: ITEM_LIFETIME,
+        }
+    }
+    /// an item placed in the map, which doesn't despawn
+    pub fn placed(item: Item, coords: Vec2) -> Self {
+        Self {
+            lifetime: i32::MAX,
+            ..Self::new(item, coords)
+        }
+    }
+    pub fn hitbox(&self) -> Rect {
+        Rect::new(self.coords.x, self.coords.y, self.wh.x, self.wh.y)
+    }
+    /// draw the item, blinking shortly before it despawns
+    pub fn draw(&self, texture: &Texture2D) {
+        if self.lifetime < ITEM_BLINK_TIME && (self.lifetime / 8) % 2 == 0 {
+            return;
+        }
+        draw_texture(texture, self.coords.x + GAME_SCREEN_MAIN.x, self.coords.y + GAME_SCREEN_MAIN.y, WHITE);
+    }
+}
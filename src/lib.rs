@@ -0,0 +1,196 @@
+// the game without its window: the gameplay simulation, the scenes and everything they share.
+// `main.rs` opens the window and runs the scenes, the tools in `bin/` use the parts they need
+
+// types are made with `new`, like everywhere in this game
+#![allow(clippy::new_without_default)]
+// fires inside nanoserde's derive for `Option` fields
+#![allow(clippy::question_mark)]
+use std::collections::HashMap;
+use macroquad::prelude::*;
+use macroquad_tiled::Map;
+use macroquad_canvas_2d::Canvas2D;
+use macroUtils::include_texture;
+use crate::assets::{live_assets, MapLoader};
+use crate::enemy::EnemyKinds;
+use crate::savegame::SaveData;
+use crate::stage::{builtin_levels, load_levels, Stage};
+use crate::world::World;
+
+pub mod assets;
+pub mod audio;
+pub mod player;
+pub mod items;
+pub mod objects;
+pub mod endless;
+pub mod enemy;
+pub mod gamepad;
+pub mod bullet;
+pub mod boss;
+pub mod collision;
+pub mod generator;
+pub mod highscores;
+pub mod input;
+pub mod pathfinding;
+pub mod render;
+pub mod savegame;
+pub mod scene;
+pub mod scenes;
+pub mod settings;
+pub mod stage;
+pub mod storage;
+pub mod terrain;
+pub mod tiled;
+pub mod world;
+
+pub const GAME_SCREEN_MAIN: Rect = Rect { x: 94., y: 0., w: 256., h: 256. };
+/// size of the canvas everything is drawn onto: the HUD left of the room and the room
+pub const CANVAS_SIZE: Vec2 = vec2(350., 256.);
+pub const SPEED: f32 = 1.;
+pub const TILE_SIZE: f32 = 16.;
+pub const STD_TIMER_MAX: i32 = 3000;
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+pub struct DebugSettings {
+    pub debug_mode: bool,
+    pub debug_menu: bool,
+    pub fixed_speed: f32,
+    pub invincibility: bool,
+    pub transition_timer_diff: f32,
+    pub spawn_multiplier: f32,
+    pub bullets_cancel: bool,
+}
+impl DebugSettings {
+    pub fn new() -> Self {
+        Self {
+            debug_menu: false,
+            debug_mode: false,
+            fixed_speed: 60.0,
+            invincibility: false,
+            transition_timer_diff: 0.5,
+            spawn_multiplier: 1.,
+            bullets_cancel: true,
+        }
+    }
+}
+
+pub struct GameState {
+    pub assets: HashMap<&'static str, Texture2D>,
+    pub tilemap: Map,
+    pub tilemap_old: Map,
+    pub map_loader: MapLoader,
+    pub canvas: Canvas2D,
+    /// textures of the enemy kinds, in the order of `world.enemy_kinds`
+    pub enemy_textures: Vec<Texture2D>,
+    pub world: World,
+    pub debug: bool,
+}
+
+impl GameState {
+    pub fn new() -> Self {
+        let mut assets = HashMap::new();
+
+        assets.insert("player", include_texture!("../assets/player.png"));
+        assets.insert("item::coffee", include_texture!("../assets/coffee.png"));
+        assets.insert("item::quickshoot", include_texture!("../assets/quickshoot.png"));
+        assets.insert("item::spread", include_texture!("../assets/spread.png"));
+        assets.insert("item::machinegun", include_texture!("../assets/machinegun.png"));
+        assets.insert("item::piercing", include_texture!("../assets/piercing.png"));
+        assets.insert("item::nuke", include_texture!("../assets/nuke.png"));
+        assets.insert("item::extra_life", include_texture!("../assets/extra_life.png"));
+        assets.insert("item::smoke_bomb", include_texture!("../assets/smoke_bomb.png"));
+        assets.insert("item::badge", include_texture!("../assets/badge.png"));
+        assets.insert("item::bouncing", include_texture!("../assets/bouncing.png"));
+        assets.insert("next_effect", include_texture!("../assets/next_effect.png"));
+        assets.insert("menu::death", include_texture!("../assets/death_text.png"));
+        assets.insert("menu::title", include_texture!("../assets/title.png"));
+        assets.insert("menu::paused", include_texture!("../assets/paused.png"));
+        assets.insert("boss", include_texture!("../assets/boss.png"));
+
+
+        let live = live_assets();
+        let mut stages = load_levels(live);
+        let mut map_loader = MapLoader::new(live);
+        let (tilemap, tilemap_old) = match Self::first_tilemaps(&mut map_loader, &stages) {
+            Some(tilemaps) => tilemaps,
+            None => {
+                println!("WARN: the first stage can't be loaded. Using the built-in levels");
+                stages = builtin_levels();
+                Self::first_tilemaps(&mut map_loader, &stages).expect("the built-in maps are broken")
+            }
+        };
+
+        let enemy_kinds = EnemyKinds::load();
+        let enemy_textures = enemy_kinds.load_textures();
+
+        let mut canvas = Canvas2D::new(CANVAS_SIZE.x, CANVAS_SIZE.y);
+        canvas.get_texture_mut().set_filter(FilterMode::Nearest);
+        GameState {
+            debug: false,
+            assets,
+            tilemap,
+            canvas,
+            enemy_textures,
+            world: World::new(stages, enemy_kinds, miniquad::date::now() as u64),
+            tilemap_old,
+            map_loader,
+
+        }
+    }
+
+    /// the tilemap of the first stage, twice: the current room and the one the transition scrolls out
+    fn first_tilemaps(map_loader: &mut MapLoader, stages: &[Stage]) -> Option<(Map, Map)> {
+        Some((map_loader.load(&stages[0])?, map_loader.load(&stages[0])?))
+    }
+
+    /// replace the new run with the saved one, at the start of the stage it was saved in.
+    /// False, and the new run is kept, if the map of that stage can't be loaded
+    pub fn continue_run(&mut self, save: SaveData) -> bool {
+        let world = save.restore(self.world.stages.clone(), self.world.enemy_kinds.clone());
+        self.start(world)
+    }
+
+    /// replace the campaign with an endless run with the rooms of `seed`.
+    /// False, and the campaign is kept, if the first room can't be loaded
+    pub fn start_endless(&mut self, seed: u64) -> bool {
+        let world = World::new_endless(seed, self.world.enemy_kinds.clone());
+        self.start(world)
+    }
+
+    /// switch to `world` if the map of its stage can be loaded
+    fn start(&mut self, world: World) -> bool {
+        let Some(tilemap) = self.map_loader.load(world.stage()) else {
+            return false;
+        };
+        self.world = world;
+        self.tilemap = tilemap;
+        true
+    }
+}
+
+
+
+pub const SHOOT_COOLDOWN_MAX: i32 = 30;
+
+/// draw text using specified font in the centre with the offset `offset`
+pub fn draw_text_centred(text: &str, font: &Font, offset: f32) {
+    let start_size = measure_text(text, Some(font), (screen_height() / 20.) as u16, 1.);
+    draw_text_ex(text, screen_width() / 2. - start_size.width / 2., screen_height() / 2. - start_size.height / 2. + offset, TextParams {
+        font: Some(font),
+        font_size: (screen_height() / 20.) as u16,
+        font_scale: 1.0,
+        font_scale_aspect: 1.0,
+        rotation: 0.0,
+        color: WHITE,
+    });
+}
+pub fn draw_text_centred_ex(text: &str, font: &Font, offset: f32, width: f32, height: f32) {
+    let start_size = measure_text(text, Some(font), (height / 20.) as u16, 1.);
+    draw_text_ex(text, width / 2. - start_size.width / 2., height / 2. - start_size.height / 2. + offset, TextParams {
+        font: Some(font),
+        font_size: (height / 20.) as u16,
+        font_scale: 1.0,
+        font_scale_aspect: 1.0,
+        rotation: 0.0,
+        color: WHITE,
+    });
+}
\ No newline at end of file
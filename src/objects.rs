@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use macroquad::prelude::*;
+use crate::items::Item;
+use crate::stage::Exit;
+use crate::tiled::{property, TiledMap, TiledObject};
+
+/// name of the object layer with the entities and triggers of a map
+pub const ENTITIES_LAYER: &str = "entities";
+
+/// a zone that takes the player to the next stage when the exit is open
+#[derive(Clone, Debug)]
+pub struct ExitZone {
+    pub rect: Rect,
+    /// the direction the rooms scroll in
+    pub direction: Exit,
+}
+
+/// enemies that spawn at a fixed place some time after the stage started
+#[derive(Clone, Debug)]
+pub struct ScriptedSpawn {
+    /// name of the enemy kind
+    pub kind: String,
+    pub coords: Vec2,
+    /// ticks after the start of the stage
+    pub delay: i32,
+    pub count: i32,
+}
+
+/// a region that does something when the player walks into it
+#[derive(Clone, Debug)]
+pub struct Trigger {
+    pub rect: Rect,
+    /// everything set on the object in Tiled. `spawn` (enemy kind) and `count` are handled by the world
+    pub properties: HashMap<String, String>,
+    /// triggers only fire once unless `repeat` is set
+    pub repeat: bool,
+    pub fired: bool,
+    /// whether the player stood in it last tick
+    pub inside: bool,
+}
+
+/// everything placed in the `entities` layer of a map. Objects are told apart by their name:
+/// `player_start`, `exit`, `item`, `enemy` and `trigger`
+#[derive(Clone, Debug, Default)]
+pub struct MapObjects {
+    pub player_start: Option<Vec2>,
+    pub exits: Vec<ExitZone>,
+    pub items: Vec<(Item, Vec2)>,
+    pub enemy_spawns: Vec<ScriptedSpawn>,
+    pub triggers: Vec<Trigger>,
+}
+
+fn object_rect(object: &TiledObject) -> Rect {
+    Rect::new(object.x, object.y, object.width, object.height)
+}
+
+/// read the `entities` layer. Maps without one get the defaults: player in the centre, the stage's edge exit
+pub fn find_objects(map: &TiledMap) -> MapObjects {
+    let mut objects = MapObjects::default();
+    for object in map.objects(ENTITIES_LAYER) {
+        let coords = vec2(object.x, object.y);
+        let properties = &object.properties;
+        match object.name.as_str() {
+            "player_start" => objects.player_start = Some(coords),
+            "exit" => {
+                let direction = property::<String>(properties, "direction").and_then(|name| Exit::from_name(&name)).unwrap_or(Exit::South);
+                objects.exits.push(ExitZone { rect: object_rect(object), direction });
+            }
+            "item" => match property::<String>(properties, "item").and_then(|name| Item::from_key(&name)) {
+                Some(item) => objects.items.push((item, coords)),
+                None => println!("WARN: item object at {}/{} has no or an unknown `item` property", coords.x, coords.y),
+            },
+            "enemy" => match property::<String>(properties, "kind") {
+                Some(kind) => objects.enemy_spawns.push(ScriptedSpawn {
+                    kind,
+                    coords,
+                    delay: property(properties, "delay").unwrap_or(0),
+                    count: property(properties, "count").unwrap_or(1),
+                }),
+                None => println!("WARN: enemy object at {}/{} has no `kind` property", coords.x, coords.y),
+            },
+            "trigger" => objects.triggers.push(Trigger {
+                rect: object_rect(object),
+                properties: properties.iter().map(|property| (property.name.clone(), property.value.0.clone())).collect(),
+                repeat: property(properties, "repeat").unwrap_or(false),
+                fired: false,
+                inside: false,
+            }),
+            name => println!("WARN: unknown object `{}` at {}/{} in the {} layer", name, coords.x, coords.y, ENTITIES_LAYER),
+        }
+    }
+    objects
+}
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use macroquad::prelude::*;
+use crate::TILE_SIZE;
+use crate::collision::CollisionType;
+
+const UNREACHABLE: u32 = u32::MAX;
+
+/// distance (in tiles) from every tile of the room to the target, so every enemy can look up
+/// which neighbouring tile brings it closer to the player without running its own search
+pub struct FlowField {
+    width: u32,
+    height: u32,
+    solid: Vec<bool>,
+    distances: Vec<u32>,
+    target: Option<(u32, u32)>,
+}
+
+impl FlowField {
+    /// an empty field without any walls
+    pub fn new() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            solid: vec![],
+            distances: vec![],
+            target: None,
+        }
+    }
+
+    /// build the field's grid from the room's collision map
+    pub fn from_collision_map(collision_map: &[CollisionType]) -> Self {
+        let mut width = 0;
+        let mut height = 0;
+        for tile in collision_map {
+            let (CollisionType::Solid(x, y) | CollisionType::Empty(x, y)) = *tile;
+            width = width.max(x + 1);
+            height = height.max(y + 1);
+        }
+        let mut solid = vec![false; (width * height) as usize];
+        for tile in collision_map {
+            if let CollisionType::Solid(x, y) = *tile {
+                solid[(y * width + x) as usize] = true;
+            }
+        }
+        Self {
+            width,
+            height,
+            solid,
+            distances: vec![UNREACHABLE; (width * height) as usize],
+            target: None,
+        }
+    }
+
+    fn tile_at(&self, coords: Vec2) -> Option<(u32, u32)> {
+        if coords.x < 0. || coords.y < 0. {
+            return None;
+        }
+        let (x, y) = ((coords.x / TILE_SIZE) as u32, (coords.y / TILE_SIZE) as u32);
+        if x < self.width && y < self.height {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+
+    fn neighbours(&self, x: u32, y: u32) -> impl Iterator<Item=(u32, u32)> + '_ {
+        [(0, -1), (1, 0), (0, 1), (-1, 0)].into_iter().filter_map(move |(dx, dy): (i32, i32)| {
+            let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+            if nx < 0 || ny < 0 || nx >= self.width as i32 || ny >= self.height as i32 || self.solid[(ny as u32 * self.width + nx as u32) as usize] {
+                None
+            } else {
+                Some((nx as u32, ny as u32))
+            }
+        })
+    }
+
+    /// recompute the distances towards `target` (a position in room coordinates). Skipped if the target stays on the same tile
+    pub fn update(&mut self, target: Vec2) {
+        let target = self.tile_at(target);
+        if target == self.target {
+            return;
+        }
+        self.target = target;
+        self.distances.fill(UNREACHABLE);
+        let Some((tx, ty)) = target else {
+            return;
+        };
+        // breadth-first search from the target over every walkable tile
+        let mut queue = VecDeque::new();
+        self.distances[(ty * self.width + tx) as usize] = 0;
+        queue.push_back((tx, ty));
+        while let Some((x, y)) = queue.pop_front() {
+            let distance = self.distances[(y * self.width + x) as usize];
+            let neighbours: Vec<(u32, u32)> = self.neighbours(x, y).collect();
+            for (nx, ny) in neighbours {
+                let index = (ny * self.width + nx) as usize;
+                if self.distances[index] == UNREACHABLE {
+                    self.distances[index] = distance + 1;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+
+    /// how many tiles away from the target `coords` is. `None` if the target can't be reached from there
+    pub fn distance(&self, coords: Vec2) -> Option<u32> {
+        let (x, y) = self.tile_at(coords)?;
+        Some(self.distances[(y * self.width + x) as usize]).filter(|distance| *distance != UNREACHABLE)
+    }
+
+    /// whether the target can be reached from `coords`
+    pub fn reaches(&self, coords: Vec2) -> bool {
+        self.distance(coords).is_some()
+    }
+
+    /// the centre of the tile to walk to from `coords` to get closer to the target.
+    /// `None` if `coords` is already on the target's tile or the target can't be reached from there
+    pub fn next_step(&self, coords: Vec2) -> Option<Vec2> {
+        let (x, y) = self.tile_at(coords)?;
+        let distance = self.distances[(y * self.width + x) as usize];
+        if distance == 0 || distance == UNREACHABLE {
+            return None;
+        }
+        let (nx, ny) = self.neighbours(x, y).min_by_key(|(nx, ny)| self.distances[(ny * self.width + nx) as usize])?;
+        Some(vec2((nx as f32 + 0.5) * TILE_SIZE, (ny as f32 + 0.5) * TILE_SIZE))
+    }
+}
@@ -1,8 +1,10 @@
 use macroquad::prelude::*;
-use crate::bullet::{Bullet, Direction};
-use crate::{GameState, GAME_SCREEN_MAIN, SHOOT_COOLDOWN_MAX, SPEED, TILE_SIZE};
+use crate::bullet::{Bullet, Direction, SPREAD_ANGLE};
+use crate::{GAME_SCREEN_MAIN, SHOOT_COOLDOWN_MAX, SPEED, TILE_SIZE};
 use crate::collision::CollisionType;
+use crate::input::InputFrame;
 use crate::items::Item;
+use crate::world::{World, WorldEvent};
 
 #[derive(Clone)]
 pub struct Player {
@@ -15,16 +17,32 @@ pub struct Player {
     pub effect_duration: i32,
     pub strength: i32,
     pub health: i32,
+    // modifiers set by the active effect every tick
+    pub speed: f32,
+    pub spread: bool,
+    pub piercing: bool,
+    pub bouncing: bool,
+    pub invincible: bool,
+    /// where enemies go instead of the player while confused by a smoke bomb
+    pub decoy: Option<Vec2>,
 }
 impl Player {
-    pub fn reset_coords(&mut self) {
-        self.coords = vec2(GAME_SCREEN_MAIN.w / 2. - 8., GAME_SCREEN_MAIN.h / 2. - 8.);
+    /// where the player starts in rooms without a `player_start` object: the centre
+    pub fn default_start() -> Vec2 {
+        vec2(GAME_SCREEN_MAIN.w / 2. - 8., GAME_SCREEN_MAIN.h / 2. - 8.)
+    }
+    pub fn reset_coords(&mut self, start: Vec2) {
+        self.coords = start;
+    }
+    /// the position enemies walk towards
+    pub fn target(&self) -> Vec2 {
+        self.decoy.unwrap_or(self.coords)
     }
 }
 impl Default for Player {
     fn default() -> Self {
         Self {
-            coords: vec2(GAME_SCREEN_MAIN.w / 2. - 8., GAME_SCREEN_MAIN.h / 2. - 8.),
+            coords: Player::default_start(),
             flipped: false,
             wh: vec2(TILE_SIZE, TILE_SIZE),
             effect: None,
@@ -33,104 +51,131 @@ impl Default for Player {
             effect_duration: 0,
             strength: 1,
             health: 3,
+            speed: SPEED,
+            spread: false,
+            piercing: false,
+            bouncing: false,
+            invincible: false,
+            decoy: None,
         }
     }
 }
 
 
-pub fn update_fixed(gs: &mut GameState) {
+pub fn update_fixed(player: &mut Player) {
     // effect runtime
-    if gs.player.effect_duration > 0 {
-        gs.player.effect_duration -= 1;
+    if player.effect_duration > 0 {
+        player.effect_duration -= 1;
     }
-    if gs.player.effect_duration == 1 {
-        gs.player.effect = None;
+    if player.effect_duration == 1 {
+        player.effect = None;
     }
-    // effects
-    if let Some(effect) = &gs.player.effect {
-        if *effect == Item::Quickshoot && gs.player.quickshoot == 0 {
-            gs.player.quickshoot = 12;
-        }
-    } else {
-        if gs.player.quickshoot != 0 {
-            gs.player.quickshoot = 0;
-        }
+    // effects: reset every modifier, then let the active effect set what it needs
+    player.quickshoot = 0;
+    player.speed = SPEED;
+    player.spread = false;
+    player.piercing = false;
+    player.bouncing = false;
+    player.invincible = false;
+    if let Some(effect) = player.effect {
+        (effect.info().on_tick)(player);
+    }
+    // the decoy only lasts as long as the smoke bomb
+    if player.effect != Some(Item::SmokeBomb) {
+        player.decoy = None;
     }
 }
-pub fn key_inputs(gs: &mut GameState, shoot_cooldown: &mut i32, collision_map: &Vec<CollisionType>) {
-    let old_coords = gs.player.coords;
+/// move the player and shoot according to `input`
+pub fn apply_input(world: &mut World, input: &InputFrame) {
+    let player = &mut world.player;
+    let old_coords = player.coords;
+    // slow terrain like sand
+    let speed = player.speed * world.terrain.slow_at(player.coords + player.wh / 2.);
     // movement
-    if is_key_down(KeyCode::W) && is_key_down(KeyCode::A) {
-        gs.player.coords.y -= SPEED.sqrt();
-        gs.player.coords.x -= SPEED.sqrt();
-        gs.player.flipped = true;
-    } else if is_key_down(KeyCode::S) && is_key_down(KeyCode::A) {
-        gs.player.coords.y += SPEED.sqrt();
-        gs.player.coords.x -= SPEED.sqrt();
-        gs.player.flipped = true;
-    } else if is_key_down(KeyCode::W) && is_key_down(KeyCode::D) {
-        gs.player.coords.y -= SPEED.sqrt();
-        gs.player.coords.x += SPEED.sqrt();
-        gs.player.flipped = false;
-    } else if is_key_down(KeyCode::S) && is_key_down(KeyCode::D) {
-        gs.player.coords.y += SPEED.sqrt();
-        gs.player.coords.x += SPEED.sqrt();
-        gs.player.flipped = false;
-    } else if is_key_down(KeyCode::W) {
-        gs.player.coords.y -= SPEED
-    } else if is_key_down(KeyCode::A) {
-        gs.player.coords.x -= SPEED;
-        gs.player.flipped = true;
-    } else if is_key_down(KeyCode::D) {
-        gs.player.coords.x += SPEED;
-        gs.player.flipped = false;
-    } else if is_key_down(KeyCode::S) {
-        gs.player.coords.y += SPEED;
+    if input.movement != Vec2::ZERO {
+        // analog stick: as fast as it's pushed
+        player.coords += input.movement * speed;
+        if input.movement.x != 0. {
+            player.flipped = input.movement.x < 0.;
+        }
+    } else if input.up && input.left {
+        player.coords.y -= speed.sqrt();
+        player.coords.x -= speed.sqrt();
+        player.flipped = true;
+    } else if input.down && input.left {
+        player.coords.y += speed.sqrt();
+        player.coords.x -= speed.sqrt();
+        player.flipped = true;
+    } else if input.up && input.right {
+        player.coords.y -= speed.sqrt();
+        player.coords.x += speed.sqrt();
+        player.flipped = false;
+    } else if input.down && input.right {
+        player.coords.y += speed.sqrt();
+        player.coords.x += speed.sqrt();
+        player.flipped = false;
+    } else if input.up {
+        player.coords.y -= speed
+    } else if input.left {
+        player.coords.x -= speed;
+        player.flipped = true;
+    } else if input.right {
+        player.coords.x += speed;
+        player.flipped = false;
+    } else if input.down {
+        player.coords.y += speed;
     }
 
-    let pc = gs.player.coords;
-    let ps = gs.player.wh;
-    for tile in collision_map {
+    for tile in &world.collision_map {
         if let CollisionType::Solid(x, y) = tile {
             // go in a bit collision-wise to make collisions feel better
-            if Rect::new(gs.player.coords.x + 3., gs.player.coords.y + 3., gs.player.wh.x - 3., gs.player.wh.y - 3.).overlaps(&Rect::new(*x as f32 * TILE_SIZE, *y as f32 * 16., 16., 16.)) {
-                gs.player.coords = old_coords;
+            if Rect::new(player.coords.x + 3., player.coords.y + 3., player.wh.x - 3., player.wh.y - 3.).overlaps(&Rect::new(*x as f32 * TILE_SIZE, *y as f32 * 16., 16., 16.)) {
+                player.coords = old_coords;
             }
         }
     }
 
     // collision with world's outer borders (x<0, y<0, y>YMAX, x>XMAX)
-    if gs.player.coords.x < 0. || gs.player.coords.y < 0. || (gs.player.coords.x + gs.player.wh.x) > GAME_SCREEN_MAIN.w || (gs.player.coords.y + gs.player.wh.y) > GAME_SCREEN_MAIN.h {
-        gs.player.coords = old_coords;
+    if player.coords.x < 0. || player.coords.y < 0. || (player.coords.x + player.wh.x) > GAME_SCREEN_MAIN.w || (player.coords.y + player.wh.y) > GAME_SCREEN_MAIN.h {
+        player.coords = old_coords;
     }
 
 
     // shooting
-    if *shoot_cooldown == 0 {
-        if is_key_down(KeyCode::Up) && is_key_down(KeyCode::Left) {
-            gs.bullets.push(Bullet::new(gs.player.coords, Direction::Leftup));
-            *shoot_cooldown = SHOOT_COOLDOWN_MAX - gs.player.quickshoot;
-        } else if is_key_down(KeyCode::Up) && is_key_down(KeyCode::Right) {
-            gs.bullets.push(Bullet::new(gs.player.coords, Direction::Rightup));
-            *shoot_cooldown = SHOOT_COOLDOWN_MAX - gs.player.quickshoot;
-        } else if is_key_down(KeyCode::Down) && is_key_down(KeyCode::Left) {
-            gs.bullets.push(Bullet::new(gs.player.coords, Direction::Leftdown));
-            *shoot_cooldown = SHOOT_COOLDOWN_MAX - gs.player.quickshoot;
-        } else if is_key_down(KeyCode::Down) && is_key_down(KeyCode::Right) {
-            gs.bullets.push(Bullet::new(gs.player.coords, Direction::Rightdown));
-            *shoot_cooldown = SHOOT_COOLDOWN_MAX - gs.player.quickshoot;
-        } else if is_key_down(KeyCode::Up) {
-            gs.bullets.push(Bullet::new(gs.player.coords, Direction::Up));
-            *shoot_cooldown = SHOOT_COOLDOWN_MAX - gs.player.quickshoot;
-        } else if is_key_down(KeyCode::Down) {
-            gs.bullets.push(Bullet::new(gs.player.coords, Direction::Down));
-            *shoot_cooldown = SHOOT_COOLDOWN_MAX - gs.player.quickshoot;
-        } else if is_key_down(KeyCode::Left) {
-            gs.bullets.push(Bullet::new(gs.player.coords, Direction::Left));
-            *shoot_cooldown = SHOOT_COOLDOWN_MAX - gs.player.quickshoot;
-        } else if is_key_down(KeyCode::Right) {
-            gs.bullets.push(Bullet::new(gs.player.coords, Direction::Right));
-            *shoot_cooldown = SHOOT_COOLDOWN_MAX - gs.player.quickshoot;
+    if world.shoot_cooldown == 0 {
+        let direction = if input.shoot_up && input.shoot_left {
+            Some(Direction::Leftup)
+        } else if input.shoot_up && input.shoot_right {
+            Some(Direction::Rightup)
+        } else if input.shoot_down && input.shoot_left {
+            Some(Direction::Leftdown)
+        } else if input.shoot_down && input.shoot_right {
+            Some(Direction::Rightdown)
+        } else if input.shoot_up {
+            Some(Direction::Up)
+        } else if input.shoot_down {
+            Some(Direction::Down)
+        } else if input.shoot_left {
+            Some(Direction::Left)
+        } else if input.shoot_right {
+            Some(Direction::Right)
+        } else {
+            None
+        };
+        let bullet = if input.aim != Vec2::ZERO {
+            Some(Bullet::aimed(player.coords, input.aim))
+        } else {
+            direction.map(|direction| Bullet::new(player.coords, direction))
+        };
+        if let Some(bullet) = bullet {
+            let bullet = bullet.piercing(player.piercing).bouncing(player.bouncing);
+            if player.spread {
+                world.bullets.push(bullet.clone().rotated(-SPREAD_ANGLE));
+                world.bullets.push(bullet.clone().rotated(SPREAD_ANGLE));
+            }
+            world.bullets.push(bullet);
+            world.shoot_cooldown = SHOOT_COOLDOWN_MAX - player.quickshoot;
+            world.events.push(WorldEvent::PlayerShot);
         }
     }
-}
\ No newline at end of file
+}
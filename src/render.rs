@@ -0,0 +1,147 @@
+use macroquad::prelude::*;
+use crate::{GameState, GAME_SCREEN_MAIN, TILE_SIZE, VERSION};
+use crate::collision::CollisionType;
+
+/// draw the room and everything in it onto the canvas. `damaged` is the index of the enemy to draw the damage animation on
+pub fn draw_world(gs: &GameState, damaged: Option<usize>) {
+    let world = &gs.world;
+    // draw tiles
+    gs.tilemap.draw_tiles("background", GAME_SCREEN_MAIN, None);
+    gs.tilemap.draw_tiles("main", GAME_SCREEN_MAIN, None);
+    gs.tilemap.draw_tiles("objects", GAME_SCREEN_MAIN, None);
+
+    // draw enemies
+    for (index, enemy) in world.enemies.iter().enumerate() {
+        enemy.draw(&gs.enemy_textures[enemy.kind], damaged == Some(index));
+    }
+
+    // draw boss
+    if let Some(boss) = &world.boss {
+        boss.draw(gs.assets.get("boss").unwrap());
+    }
+
+    // draw items
+    for item in &world.items {
+        item.draw(gs.assets.get(item.item.texture_name()).unwrap());
+    }
+
+    // draw bullets
+    for bullet in &world.bullets {
+        bullet.draw();
+    }
+    for impact in &world.impacts {
+        impact.draw();
+    }
+
+    // arrows at the open exits, so the player knows where the paths lead out of the room
+    if world.exit_open() && get_time().fract() < 0.5 {
+        let centre = vec2(GAME_SCREEN_MAIN.w, GAME_SCREEN_MAIN.h) / 2.;
+        let arrows: Vec<(Vec2, Vec2)> = if world.objects.exits.is_empty() {
+            world.stage().exits.iter().map(|(exit, _)| (centre + exit.direction() * (centre - 6.), exit.direction())).collect()
+        } else {
+            world.objects.exits.iter().map(|exit| (exit.rect.center(), exit.direction.direction())).collect()
+        };
+        let offset = vec2(GAME_SCREEN_MAIN.x, GAME_SCREEN_MAIN.y);
+        for (tip, direction) in arrows {
+            let side = direction.perp() * 4.;
+            let base = tip - direction * 4.;
+            draw_triangle(offset + tip, offset + base + side, offset + base - side, YELLOW);
+        }
+    }
+
+    // draw player
+    draw_texture_ex(
+        gs.assets.get("player").unwrap(),
+        world.player.coords.x + GAME_SCREEN_MAIN.x,
+        world.player.coords.y + GAME_SCREEN_MAIN.y,
+        if world.player.invincible { GOLD } else { WHITE },
+        DrawTextureParams {
+            flip_x: world.player.flipped,
+            ..Default::default()
+        },
+    );
+
+    // draw debug hitboxes
+    if gs.debug {
+        let player = &world.player;
+        // draw player hitbox
+        draw_circle(player.coords.x + GAME_SCREEN_MAIN.x, player.coords.y + GAME_SCREEN_MAIN.y, 2., GREEN);
+        draw_rectangle_lines(player.coords.x + GAME_SCREEN_MAIN.x, player.coords.y + GAME_SCREEN_MAIN.y, player.wh.x, player.wh.y, 1., GREEN);
+        draw_rectangle(player.coords.x + GAME_SCREEN_MAIN.x, player.coords.y + player.wh.y + GAME_SCREEN_MAIN.y, player.wh.x, 1., RED);
+
+        // draw map hitbox
+        for tile in &world.collision_map {
+            if let CollisionType::Solid(x, y) = tile {
+                draw_rectangle_lines(*x as f32 * TILE_SIZE + GAME_SCREEN_MAIN.x, *y as f32 * TILE_SIZE + GAME_SCREEN_MAIN.y, TILE_SIZE, TILE_SIZE, 2., RED);
+            }
+        }
+
+        // enemy hitboxes
+        for enemy in &world.enemies {
+            draw_rectangle_lines(GAME_SCREEN_MAIN.x + enemy.coords.x, enemy.coords.y, enemy.wh.x, enemy.wh.y, 1., BLUE);
+        }
+        if let Some(boss) = &world.boss {
+            draw_rectangle_lines(GAME_SCREEN_MAIN.x + boss.coords.x, boss.coords.y, boss.wh.x, boss.wh.y, 1., BLUE);
+        }
+
+        // exit zones and triggers of the map
+        for exit in &world.objects.exits {
+            draw_rectangle_lines(GAME_SCREEN_MAIN.x + exit.rect.x, exit.rect.y, exit.rect.w, exit.rect.h, 1., YELLOW);
+        }
+        for trigger in &world.objects.triggers {
+            draw_rectangle_lines(GAME_SCREEN_MAIN.x + trigger.rect.x, trigger.rect.y, trigger.rect.w, trigger.rect.h, 1., if trigger.fired { GRAY } else { MAGENTA });
+        }
+    }
+}
+
+/// draw the HUD left of the room onto the canvas
+pub fn draw_hud(gs: &GameState) {
+    let world = &gs.world;
+    // effect duration
+    if world.player.effect_duration > 0 {
+        draw_rectangle(10., 19. - (world.player.effect_duration / 64) as f32 / 2., 5., (world.player.effect_duration / 64) as f32, if (world.player.effect_duration / 64) / 2 <= 1 { RED } else { GREEN });
+    }
+    // boss health, or time to play the stage for
+    if let Some(boss) = &world.boss {
+        boss.draw_health_bar();
+    } else if world.stage_timer > 0 && !world.stage().boss {
+        draw_rectangle(10., 80. - (world.stage_timer / 64) as f32 / 2., 5., (world.stage_timer / 64) as f32, if (world.stage_timer / 64) / 2 <= 1 { RED } else { GREEN });
+    }
+    // enemies left to kill before the exit opens
+    let kills_left = world.stage().kill_goal - world.stage_kills;
+    if kills_left > 0 && !world.stage().boss {
+        let height = 40. * kills_left as f32 / world.stage().kill_goal as f32;
+        draw_rectangle(17., 80. - height / 2., 5., height, PURPLE);
+    }
+
+    // next effect background
+    draw_texture_ex(
+        gs.assets.get("next_effect").unwrap(),
+        20.,
+        10.,
+        WHITE,
+        DrawTextureParams { dest_size: Some(vec2(20., 20.)), ..Default::default() },
+    );
+
+    // next effect
+    if let Some(item) = world.player.held_effect {
+        draw_texture(gs.assets.get(item.texture_name()).unwrap(), 22., 12., WHITE);
+    }
+    // player HP
+    for i in 0..world.player.health {
+        draw_rectangle(i as f32 * 6., 25., 5., 5., DARKGREEN);
+    }
+    // wave of the endless run
+    if let Some(endless) = &world.endless {
+        draw_text(format!("Wave {}", endless.wave + 1).as_str(), 4., 110., 16., WHITE);
+    }
+}
+
+/// draw the debug text in screen coordinates, after the canvas has been drawn
+pub fn draw_debug_hud(gs: &GameState) {
+    let world = &gs.world;
+    draw_text(format!("JOTMK v{}, FPS: {}", VERSION, get_fps()).as_str(), 10., 10., 20., WHITE);
+    draw_text(format!("Player Coords: {}/{}", world.player.coords.x as i32, world.player.coords.y as i32).as_str(), 10., 25., 20., WHITE);
+    draw_text(format!("-> Tile: {}/{}", (world.player.coords.x / TILE_SIZE) as i32, (world.player.coords.y / TILE_SIZE) as i32).as_str(), 10., 40., 20., WHITE);
+    draw_text(format!("{} enemies, {} bullets", world.enemies.len(), world.bullets.len()).as_str(), 10., 60., 20., WHITE);
+}
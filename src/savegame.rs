@@ -0,0 +1,127 @@
+use nanoserde::{DeJson, SerJson};
+use crate::enemy::EnemyKinds;
+use crate::items::Item;
+use crate::stage::Stage;
+use crate::storage::{read_data, remove_data, write_data};
+use crate::world::World;
+
+/// file in the data dir the run is saved to when quitting
+pub const SAVE_FILE: &str = "save.json";
+/// bumped whenever `SaveData` changes, older saves are ignored
+pub const SAVE_VERSION: u32 = 2;
+
+/// what's left of a run when it's saved: enough to continue at the start of the stage it was saved in
+#[derive(DeJson, SerJson, Debug)]
+pub struct SaveData {
+    pub version: u32,
+    pub seed: u64,
+    pub endless: bool,
+    /// index in the stage list. Endless runs continue at wave `stages_cleared`
+    pub stage: usize,
+    /// map of the stage, to notice when the stage list changed since
+    pub map: String,
+    pub stages_cleared: i32,
+    pub health: i32,
+    /// keys of the items, see `Item::key`
+    pub held_item: Option<String>,
+    pub effect: Option<String>,
+    pub effect_duration: i32,
+    pub kills: i32,
+    pub score: i32,
+    /// kills towards the stage's kill goal
+    pub stage_kills: i32,
+    /// whether the no-hit bonus of the stage is already lost
+    pub stage_hurt: bool,
+    /// for the time bonus at the end of the campaign
+    pub run_ticks: i32,
+}
+
+impl SaveData {
+    pub fn from_world(world: &World) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            seed: world.seed,
+            endless: world.endless.is_some(),
+            stage: world.current_stage,
+            map: world.stage().map_path.clone(),
+            stages_cleared: world.stages_cleared,
+            health: world.player.health,
+            held_item: world.player.held_effect.map(|item| item.key().to_string()),
+            effect: world.player.effect.map(|item| item.key().to_string()),
+            effect_duration: world.player.effect_duration,
+            kills: world.enemies_killcount,
+            score: world.score,
+            stage_kills: world.stage_kills,
+            stage_hurt: world.stage_hurt,
+            run_ticks: world.run_ticks,
+        }
+    }
+
+    /// whether there's a run to continue. Doesn't check if it can be read
+    pub fn exists() -> bool {
+        read_data(SAVE_FILE).is_some()
+    }
+
+    /// read the saved run. `None` if there isn't one, or it's broken or from another version
+    pub fn load() -> Option<Self> {
+        Self::from_json(&read_data(SAVE_FILE)?)
+    }
+
+    /// parse a saved run. `None` if it's broken or from another version
+    pub fn from_json(json: &str) -> Option<Self> {
+        match SaveData::deserialize_json(json) {
+            Ok(save) if save.version == SAVE_VERSION => Some(save),
+            Ok(save) => {
+                println!("WARN: {} is from save version {}, this game reads version {}", SAVE_FILE, save.version, SAVE_VERSION);
+                None
+            }
+            Err(err) => {
+                println!("WARN: couldn't parse {}: {}", SAVE_FILE, err);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        write_data(SAVE_FILE, &self.serialize_json());
+    }
+
+    /// a run can only be continued once
+    pub fn delete() {
+        remove_data(SAVE_FILE);
+    }
+
+    /// the world of the saved run, at the start of its stage
+    pub fn restore(self, stages: Vec<Stage>, enemy_kinds: EnemyKinds) -> World {
+        let mut world = if self.endless {
+            let mut world = World::new_endless(self.seed, enemy_kinds);
+            let endless = world.endless.as_mut().unwrap();
+            endless.wave = self.stages_cleared;
+            world.stages[0] = endless.stage(&world.enemy_kinds);
+            world
+        } else {
+            let mut world = World::new(stages, enemy_kinds, self.seed);
+            // the index first, the same map can be in the list more than once
+            if world.stages.get(self.stage).is_some_and(|stage| stage.map_path == self.map) {
+                world.current_stage = self.stage;
+            } else if let Some(index) = world.stages.iter().position(|stage| stage.map_path == self.map) {
+                world.current_stage = index;
+            } else {
+                println!("WARN: the saved stage {} isn't in the level list anymore, continuing at the start", self.map);
+            }
+            world
+        };
+        world.stage_timer = world.stage().timer;
+        world.stages_cleared = self.stages_cleared;
+        world.player.health = self.health;
+        world.player.held_effect = self.held_item.and_then(|key| Item::from_key(&key));
+        world.player.effect = self.effect.and_then(|key| Item::from_key(&key));
+        world.player.effect_duration = if world.player.effect.is_some() { self.effect_duration } else { 0 };
+        world.enemies_killcount = self.kills;
+        world.score = self.score;
+        world.stage_kills = self.stage_kills;
+        world.stage_hurt = self.stage_hurt;
+        world.run_ticks = self.run_ticks;
+        world
+    }
+}
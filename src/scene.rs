@@ -0,0 +1,115 @@
+use macroquad::prelude::*;
+use crate::{DebugSettings, GameState};
+use crate::audio::Audio;
+use crate::gamepad::Gamepads;
+use crate::input::Action;
+use crate::settings::Settings;
+
+/// everything a scene may read or change that outlives the scene itself
+pub struct Context {
+    pub gs: GameState,
+    pub debug_settings: DebugSettings,
+    pub settings: Settings,
+    pub audio: Audio,
+    pub gamepads: Gamepads,
+    pub font: Font,
+    /// what went wrong, e.g. a map that couldn't be loaded. Shown by the main menu until the next menu item is picked
+    pub error: Option<String>,
+}
+
+impl Context {
+    /// whether a key or gamepad button of `action` was pressed this frame
+    pub fn pressed(&self, action: Action) -> bool {
+        self.settings.keybindings.pressed(action) || self.gamepads.action_pressed(action)
+    }
+}
+
+/// what the scene stack should do after a scene has been updated
+pub enum Transition {
+    /// keep the current scene
+    None,
+    /// put a new scene on top of the current one
+    Push(Box<dyn Scene>),
+    /// remove the current scene, returning to the one below
+    Pop,
+    /// swap the current scene for a new one
+    Replace(Box<dyn Scene>),
+    /// exit the game
+    Quit,
+}
+
+/// a screen of the game (main menu, gameplay, pause menu, ...)
+pub trait Scene {
+    /// called when the scene is put on the stack
+    fn enter(&mut self, _ctx: &mut Context) {}
+    /// called once per frame, only for the scene on top of the stack
+    fn update(&mut self, ctx: &mut Context) -> Transition;
+    /// called once per frame for the top scene and every scene it overlays
+    fn draw(&self, ctx: &Context);
+    /// called when the scene is removed from the stack
+    fn exit(&mut self, _ctx: &mut Context) {}
+    /// called when the scene above it was removed and this one is on top again
+    fn resume(&mut self, _ctx: &mut Context) {}
+    /// if true, the scene below is drawn first and this scene is drawn on top of it
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+}
+
+impl SceneStack {
+    pub fn new(mut first: Box<dyn Scene>, ctx: &mut Context) -> Self {
+        first.enter(ctx);
+        Self { scenes: vec![first] }
+    }
+
+    /// update the top scene and apply its transition. Returns false if the game should exit
+    pub fn update(&mut self, ctx: &mut Context) -> bool {
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.update(ctx),
+            None => return false,
+        };
+        match transition {
+            Transition::None => {}
+            Transition::Push(mut scene) => {
+                scene.enter(ctx);
+                self.scenes.push(scene);
+            }
+            Transition::Pop => {
+                if let Some(mut scene) = self.scenes.pop() {
+                    scene.exit(ctx);
+                }
+                if let Some(scene) = self.scenes.last_mut() {
+                    scene.resume(ctx);
+                }
+            }
+            Transition::Replace(mut scene) => {
+                if let Some(mut old) = self.scenes.pop() {
+                    old.exit(ctx);
+                }
+                scene.enter(ctx);
+                self.scenes.push(scene);
+            }
+            Transition::Quit => {
+                while let Some(mut scene) = self.scenes.pop() {
+                    scene.exit(ctx);
+                }
+            }
+        }
+        !self.scenes.is_empty()
+    }
+
+    /// draw the top scene and, if it is an overlay, everything visible below it
+    pub fn draw(&self, ctx: &Context) {
+        let mut first_visible = self.scenes.len().saturating_sub(1);
+        while first_visible > 0 && self.scenes[first_visible].is_overlay() {
+            first_visible -= 1;
+        }
+        for scene in &self.scenes[first_visible..] {
+            scene.draw(ctx);
+        }
+    }
+}
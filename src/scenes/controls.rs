@@ -0,0 +1,132 @@
+use macroquad::prelude::*;
+use crate::input::{is_bindable, key_name, Action};
+use crate::scene::{Context, Scene, Transition};
+
+/// rows of the screen after the actions
+const EXTRA_ITEMS: [&str; 2] = ["Reset to Defaults", "Back"];
+
+////////////////////////////////
+//
+// C O N T R O L S
+//
+////////////////////////////////
+/// lists the keys of every action and lets the player add and remove them
+pub struct ControlsScene {
+    selected: usize,
+    /// the next key pressed gets bound to the selected action
+    waiting: bool,
+    /// why the last key couldn't be bound
+    message: Option<String>,
+}
+
+impl ControlsScene {
+    pub fn new() -> Self {
+        Self {
+            selected: 0,
+            waiting: false,
+            message: None,
+        }
+    }
+
+    fn items() -> usize {
+        Action::ALL.len() + EXTRA_ITEMS.len()
+    }
+
+    /// the action in the selected row, `None` for the rows after the actions
+    fn selected_action(&self) -> Option<Action> {
+        Action::ALL.get(self.selected).copied()
+    }
+
+    /// bind the key pressed this frame to the selected action. Escape or the gamepad's back button cancel
+    fn capture(&mut self, ctx: &mut Context) {
+        if ctx.gamepads.action_pressed(Action::Back) {
+            self.waiting = false;
+            return;
+        }
+        let Some(key) = get_last_key_pressed() else {
+            return;
+        };
+        self.waiting = false;
+        let action = self.selected_action().unwrap();
+        if key == KeyCode::Escape {
+            return;
+        }
+        if !is_bindable(key) {
+            self.message = Some(format!("{} can't be bound", key_name(key)));
+        } else if let Err(other) = ctx.settings.keybindings.bind(action, key) {
+            self.message = Some(format!("{} is already used by {}", key_name(key), other.label()));
+        }
+    }
+}
+
+impl Scene for ControlsScene {
+    fn update(&mut self, ctx: &mut Context) -> Transition {
+        if self.waiting {
+            self.capture(ctx);
+            return Transition::None;
+        }
+        let (confirm, back, up, down) = (ctx.pressed(Action::Confirm), ctx.pressed(Action::Back), ctx.pressed(Action::MenuUp), ctx.pressed(Action::MenuDown));
+        if back || (confirm && self.selected == Self::items() - 1) {
+            ctx.settings.save();
+            return Transition::Pop;
+        }
+        if confirm {
+            self.message = None;
+            if self.selected_action().is_some() {
+                self.waiting = true;
+            } else {
+                ctx.settings.keybindings.reset();
+            }
+        } else if is_key_pressed(KeyCode::Backspace) {
+            if let Some(action) = self.selected_action() {
+                ctx.settings.keybindings.unbind_last(action);
+            }
+        }
+        if up {
+            if self.selected != 0 {
+                self.selected -= 1;
+            } else {
+                self.selected = Self::items() - 1;
+            }
+        } else if down {
+            self.selected += 1;
+            if self.selected >= Self::items() {
+                self.selected = 0
+            }
+        }
+        Transition::None
+    }
+
+    fn draw(&self, ctx: &Context) {
+        clear_background(BLACK);
+        let keys = &ctx.settings.keybindings;
+        let line = screen_height() / 25.;
+        let params = |color: Color| TextParams {
+            font: Some(&ctx.font),
+            font_size: (screen_height() / 32.) as u16,
+            color,
+            ..Default::default()
+        };
+        draw_text_ex("- Controls -", screen_width() * 0.1, line * 1.5, params(WHITE));
+        for i in 0..Self::items() {
+            let y = line * (3. + i as f32);
+            let color = if i == self.selected { YELLOW } else { WHITE };
+            let prefix = if i == self.selected { "> " } else { "  " };
+            match Action::ALL.get(i) {
+                Some(action) => {
+                    draw_text_ex(format!("{}{}", prefix, action.label()).as_str(), screen_width() * 0.1, y, params(color));
+                    let bound = if self.waiting && i == self.selected { "press a key...".to_string() } else { keys.describe(*action) };
+                    draw_text_ex(bound.as_str(), screen_width() * 0.6, y, params(color));
+                }
+                None => {
+                    draw_text_ex(format!("{}{}", prefix, EXTRA_ITEMS[i - Action::ALL.len()]).as_str(), screen_width() * 0.1, y, params(color));
+                }
+            }
+        }
+        let help = match &self.message {
+            Some(message) => message.clone(),
+            None => format!("[{}] add a key, [Backspace] remove the last one", keys.hint(Action::Confirm)),
+        };
+        draw_text_ex(help.as_str(), screen_width() * 0.1, line * (4. + Self::items() as f32), params(GRAY));
+    }
+}
@@ -0,0 +1,48 @@
+use macroquad::prelude::*;
+use crate::{GameState, GAME_SCREEN_MAIN};
+use crate::audio::{DEATH_MUSIC, MUSIC_FADE};
+use crate::input::Action;
+use crate::scene::{Context, Scene, Transition};
+use crate::scenes::highscores::NameEntry;
+use crate::world::World;
+
+/////////////////////////////////
+//
+// D E A T H   S T A T E
+//
+////////////////////////////////
+pub struct DeathScene {
+    name_entry: NameEntry,
+}
+
+impl DeathScene {
+    pub fn new(world: &World) -> Self {
+        Self { name_entry: NameEntry::new(world, false) }
+    }
+}
+
+impl Scene for DeathScene {
+    fn enter(&mut self, ctx: &mut Context) {
+        ctx.audio.play_music(DEATH_MUSIC, MUSIC_FADE);
+    }
+
+    fn update(&mut self, ctx: &mut Context) -> Transition {
+        if self.name_entry.update() {
+            return Transition::None;
+        }
+        if ctx.pressed(Action::Confirm) {
+            ctx.gs = GameState::new();
+            return Transition::Pop;
+        }
+        Transition::None
+    }
+
+    fn draw(&self, ctx: &Context) {
+        clear_background(BLACK);
+        ctx.gs.canvas.set_camera();
+        draw_texture(ctx.gs.assets.get("menu::death").unwrap(), GAME_SCREEN_MAIN.x + 15. + 2., (GAME_SCREEN_MAIN.y + GAME_SCREEN_MAIN.h) / 2. - /*27: Font size*/48. / 2. + 2., BLACK);
+        draw_texture(ctx.gs.assets.get("menu::death").unwrap(), GAME_SCREEN_MAIN.x + 15., (GAME_SCREEN_MAIN.y + GAME_SCREEN_MAIN.h) / 2. - /*27: Font size*/48. / 2., WHITE);
+        ctx.gs.canvas.draw_to_screen();
+        self.name_entry.draw(ctx, 100.);
+    }
+}
@@ -0,0 +1,300 @@
+use macroquad::prelude::*;
+use nanoserde::DeJson;
+use macroUtils::timemanager::TimeManager;
+use crate::{GameState, CANVAS_SIZE, GAME_SCREEN_MAIN};
+use crate::audio::MUSIC_FADE;
+use crate::input::{Action, InputFrame};
+use crate::render::{draw_debug_hud, draw_hud, draw_world};
+use crate::scene::{Context, Scene, Transition};
+use crate::scenes::death::DeathScene;
+use crate::scenes::pause::PauseScene;
+use crate::scenes::results::ResultsScene;
+use crate::scenes::won::WonScene;
+use crate::tiled::TiledMap;
+use crate::world::WorldEvent;
+
+/// seconds between two checks whether the map files changed, in asset path mode
+const HOT_RELOAD_INTERVAL: f32 = 0.5;
+/// frames the room shakes for when the player gets hurt
+const SHAKE_FRAMES: i32 = 12;
+
+//////////////////////////////
+//
+// G A M E
+//
+//////////////////////////////
+pub struct GameScene {
+    fixed_update_time: f32,
+    // if it is some(x), draw damage animation on enemy x
+    draw_damage_animation: Option<usize>,
+    time_handle: TimeManager<Option<usize>>,
+    // if it is some(x), the stage transition is running and the new room is x pixels away
+    transition_timer: Option<f32>,
+    // the direction the rooms scroll in during the transition
+    transition_direction: Vec2,
+    // seconds since the map files were last checked for changes
+    hot_reload_timer: f32,
+    // frames left of the screen shake
+    shake_timer: i32,
+    // draws the room into a render target of its own while the screen shakes, which then goes onto the canvas a few pixels off
+    shake_camera: Camera2D,
+    // the item key was pressed, but no fixed tick has run since
+    use_item: bool,
+}
+
+impl GameScene {
+    pub fn new() -> Self {
+        Self {
+            fixed_update_time: 0.,
+            draw_damage_animation: None,
+            time_handle: TimeManager::new(),
+            transition_timer: None,
+            transition_direction: vec2(0., 1.),
+            hot_reload_timer: 0.,
+            shake_timer: 0,
+            shake_camera: Self::shake_camera(),
+            use_item: false,
+        }
+    }
+
+    /// a camera like the canvas', with a render target of the canvas' size
+    fn shake_camera() -> Camera2D {
+        let target = render_target(CANVAS_SIZE.x as u32, CANVAS_SIZE.y as u32);
+        target.texture.set_filter(FilterMode::Nearest);
+        let mut camera = Camera2D::from_display_rect(Rect::new(0., 0., CANVAS_SIZE.x, CANVAS_SIZE.y));
+        camera.render_target = Some(target);
+        // flipped like the canvas does it, so the target isn't drawn upside down
+        camera.zoom.y = -camera.zoom.y;
+        camera
+    }
+
+    /// how far the room is moved while the screen shakes. Jumps a few pixels around, without touching the world's random numbers
+    fn shake_offset(&self) -> Vec2 {
+        if self.shake_timer > 0 {
+            vec2(((self.shake_timer * 7) % 5 - 2) as f32, ((self.shake_timer * 3) % 5 - 2) as f32)
+        } else {
+            Vec2::ZERO
+        }
+    }
+
+    /// hand the terrain, the enemy spawnpoints and the objects of the current tilemap to the world
+    fn load_stage(&mut self, ctx: &mut Context) {
+        let gs = &mut ctx.gs;
+        // the maps were checked by `load_levels` and the map loader, so this only warns about something that can't happen
+        match TiledMap::deserialize_json(&gs.world.stage().map) {
+            Ok(tiled) => gs.world.load_stage(&tiled),
+            Err(err) => println!("WARN: couldn't parse map {}: {}", gs.world.stage().map_path, err),
+        }
+    }
+
+    /// if the current map or its tilesets were saved, rebuild the tilemap, collision map and spawnpoints in place
+    fn hot_reload(&mut self, ctx: &mut Context) {
+        let gs = &mut ctx.gs;
+        if !gs.map_loader.changed() {
+            return;
+        }
+        let stage = &mut gs.world.stages[gs.world.current_stage];
+        if let Some(tilemap) = gs.map_loader.reload(stage) {
+            println!("[INFO] Reloaded map {}", stage.map_path);
+            gs.tilemap = tilemap;
+            if let Ok(tiled) = TiledMap::deserialize_json(&stage.map) {
+                gs.world.reload_map(&tiled);
+            }
+        }
+    }
+
+    /// draw the old room scrolling out and the new room scrolling in
+    fn draw_transition(&self, ctx: &Context, transition_timer: f32) {
+        let gs = &ctx.gs;
+        gs.canvas.set_camera();
+        clear_background(BLACK);
+        let direction = self.transition_direction;
+        let mut old_tilemap = GAME_SCREEN_MAIN;
+        old_tilemap.x += direction.x * (transition_timer.round() - GAME_SCREEN_MAIN.w);  // offset view of old room
+        old_tilemap.y += direction.y * (transition_timer.round() - GAME_SCREEN_MAIN.h);
+        // draw old room
+        gs.tilemap_old.draw_tiles("background", old_tilemap, None);
+        gs.tilemap_old.draw_tiles("main", old_tilemap, None);
+        gs.tilemap_old.draw_tiles("objects", old_tilemap, None);
+
+        let mut new_tilemap = GAME_SCREEN_MAIN;
+        new_tilemap.x += direction.x * transition_timer.round();            // offset view of new room
+        new_tilemap.y += direction.y * transition_timer.round();
+        // draw new room
+        gs.tilemap.draw_tiles("background", new_tilemap, None);
+        gs.tilemap.draw_tiles("main", new_tilemap, None);
+        gs.tilemap.draw_tiles("objects", new_tilemap, None);
+
+        // draw player: waits at the exit of the old room, then moves into the new one with it
+        let player = gs.world.player.coords + direction * transition_timer.round().min(GAME_SCREEN_MAIN.h / 2. - 10.);
+        draw_texture_ex(
+            gs.assets.get("player").unwrap(),
+            player.x + GAME_SCREEN_MAIN.x,
+            player.y + GAME_SCREEN_MAIN.y,
+            WHITE,
+            DrawTextureParams {
+                flip_x: gs.world.player.flipped,
+                ..Default::default()
+            },
+        );
+        // the rooms scroll past the edge of the room on the left, cover them with the HUD strip
+        draw_rectangle(0., 0., GAME_SCREEN_MAIN.x, CANVAS_SIZE.y, BLACK);
+        draw_hud(gs);
+        gs.canvas.draw_to_screen();
+    }
+}
+
+impl Scene for GameScene {
+    fn enter(&mut self, ctx: &mut Context) {
+        self.load_stage(ctx);
+        ctx.audio.play_music(&ctx.gs.world.stage().music, MUSIC_FADE);
+    }
+
+    fn update(&mut self, ctx: &mut Context) -> Transition {
+        //
+        // T R A N S I T I O N
+        //
+        if let Some(transition_timer) = &mut self.transition_timer {
+            // increase offset
+            *transition_timer -= ctx.debug_settings.transition_timer_diff;
+            if *transition_timer <= 0. {
+                self.transition_timer = None;
+            }
+            return Transition::None;
+        }
+
+        self.time_handle.update(&mut self.draw_damage_animation);
+        self.shake_timer = (self.shake_timer - 1).max(0);
+
+        // pick up changes to the map files
+        self.hot_reload_timer += get_frame_time();
+        if self.hot_reload_timer >= HOT_RELOAD_INTERVAL {
+            self.hot_reload_timer = 0.;
+            self.hot_reload(ctx);
+        }
+
+        // debug key
+        if is_key_pressed(KeyCode::F3) {
+            ctx.gs.debug = !ctx.gs.debug;
+        }
+        // switch to Pause menu
+        if ctx.pressed(Action::Pause) {
+            return Transition::Push(Box::new(PauseScene));
+        }
+
+        //
+        // F I X E D  U P D A T E
+        //
+        ctx.gs.world.spawn_multiplier = ctx.debug_settings.spawn_multiplier;
+        ctx.gs.world.invincibility = ctx.debug_settings.invincibility;
+        ctx.gs.world.bullets_cancel = ctx.debug_settings.bullets_cancel;
+        let mouse = if ctx.settings.mouse_aim && is_mouse_button_down(MouseButton::Left) {
+            // the cursor on the canvas, relative to the centre of the player where it's drawn, which moves while the screen shakes
+            let (x, y) = ctx.gs.canvas.screen_mouse_position_to_canvas(0., 0.);
+            let player = &ctx.gs.world.player;
+            let centre = vec2(GAME_SCREEN_MAIN.x, GAME_SCREEN_MAIN.y) + player.coords + player.wh / 2. + self.shake_offset();
+            Some(vec2(x, y) - centre)
+        } else {
+            None
+        };
+        let mut input = InputFrame::poll(&ctx.settings, &ctx.gamepads, mouse);
+        // a press goes to the next tick only, even if this frame runs none or several
+        self.use_item |= input.use_item;
+        self.fixed_update_time += get_frame_time();
+        while self.fixed_update_time >= 1. / ctx.debug_settings.fixed_speed {
+            input.use_item = std::mem::take(&mut self.use_item);
+            ctx.gs.world.step(input);
+            self.fixed_update_time -= 1. / ctx.debug_settings.fixed_speed;
+
+            let events = ctx.gs.world.drain_events();
+            ctx.audio.play_events(&events, &ctx.settings);
+            for event in events {
+                match event {
+                    WorldEvent::EnemyHit(index) => {
+                        self.draw_damage_animation = Some(index);
+                        self.time_handle.delay(0.1, |draw_damage_animation| { *draw_damage_animation = None; });
+                    }
+                    WorldEvent::PlayerHurt if ctx.settings.screen_shake => {
+                        self.shake_timer = SHAKE_FRAMES;
+                    }
+                    WorldEvent::TileDestroyed(x, y, name) => {
+                        // the broken tile disappears from its layer, uncovering the ones below
+                        if let Some(layer) = ctx.gs.tilemap.layers.get_mut(name) {
+                            let index = (y * layer.width + x) as usize;
+                            if index < layer.data.len() {
+                                layer.data[index] = None;
+                            }
+                        }
+                    }
+                    //
+                    // D E A T H
+                    //
+                    WorldEvent::PlayerDied if ctx.gs.world.endless.is_some() => return Transition::Replace(Box::new(ResultsScene::new(&ctx.gs.world))),
+                    WorldEvent::PlayerDied => return Transition::Replace(Box::new(DeathScene::new(&ctx.gs.world))),
+                    WorldEvent::Won => return Transition::Replace(Box::new(WonScene::new(&ctx.gs.world))),
+                    //
+                    // S T A G E   T R A N S I T I O N
+                    //
+                    WorldEvent::StageCleared(exit) => {
+                        let gs = &mut ctx.gs;
+                        let Some(tilemap) = gs.map_loader.load(gs.world.stage()) else {
+                            // back to the menu instead of a room that can't be drawn
+                            ctx.error = Some(format!("Couldn't load the map {}", gs.world.stage().map_path));
+                            ctx.gs = GameState::new();
+                            return Transition::Pop;
+                        };
+                        gs.tilemap_old = std::mem::replace(&mut gs.tilemap, tilemap);
+                        // reset collision, spawnpoints, ..
+                        self.load_stage(ctx);
+                        self.transition_timer = Some(GAME_SCREEN_MAIN.h);
+                        self.transition_direction = exit.direction();
+                        // the music crossfades while the rooms scroll, which moves a fixed distance every frame
+                        let transition_seconds = GAME_SCREEN_MAIN.h / ctx.debug_settings.transition_timer_diff * get_frame_time();
+                        ctx.audio.play_music(&ctx.gs.world.stage().music, transition_seconds);
+                        self.fixed_update_time = 0.;
+                    }
+                    _ => {}
+                }
+            }
+            if self.transition_timer.is_some() {
+                break;
+            }
+        }
+        //
+        // F I X E D  E N D
+        //
+        Transition::None
+    }
+
+    fn draw(&self, ctx: &Context) {
+        if let Some(transition_timer) = self.transition_timer {
+            self.draw_transition(ctx, transition_timer);
+            return;
+        }
+        let gs = &ctx.gs;
+
+        gs.canvas.set_camera();
+        clear_background(BLACK);
+        if self.shake_timer > 0 {
+            set_camera(&self.shake_camera);
+            clear_background(BLACK);
+            draw_world(gs, self.draw_damage_animation);
+            gs.canvas.set_camera();
+            let offset = self.shake_offset();
+            draw_texture(&self.shake_camera.render_target.as_ref().unwrap().texture, offset.x, offset.y, WHITE);
+        } else {
+            draw_world(gs, self.draw_damage_animation);
+        }
+        // HUD
+        draw_hud(gs);
+
+        // draw canvas to screen, continue without scaled screen
+        gs.canvas.draw_to_screen();
+
+
+        // debug HUD
+        if gs.debug {
+            draw_debug_hud(gs);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use macroquad::prelude::*;
+use crate::draw_text_centred;
+use crate::highscores::{HighScore, HighScores, MAX_NAME_LEN};
+use crate::input::Action;
+use crate::scene::{Context, Scene, Transition};
+use crate::world::World;
+
+/// asks for the player's name on the end screens if the run made it into the high score table
+pub struct NameEntry {
+    entry: Option<HighScore>,
+    name: String,
+    /// place in the table once the name was entered
+    place: Option<usize>,
+}
+
+impl NameEntry {
+    pub fn new(world: &World, won: bool) -> Self {
+        let entry = HighScore::from_world(world, won);
+        // forget the keys typed while playing
+        clear_input_queue();
+        Self {
+            entry: if HighScores::load().qualifies(entry.score) { Some(entry) } else { None },
+            name: String::new(),
+            place: None,
+        }
+    }
+
+    /// take the typed keys. Returns true while the name is being entered, the scene shouldn't react to keys then
+    pub fn update(&mut self) -> bool {
+        if self.entry.is_none() {
+            return false;
+        }
+        while let Some(character) = get_char_pressed() {
+            if (character.is_ascii_alphanumeric() || character == '-' || character == '_') && self.name.len() < MAX_NAME_LEN {
+                self.name.push(character);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.name.pop();
+        }
+        if is_key_pressed(KeyCode::Enter) && !self.name.is_empty() {
+            let mut entry = self.entry.take().unwrap();
+            entry.name = self.name.clone();
+            let mut highscores = HighScores::load();
+            self.place = highscores.insert(entry);
+            highscores.save();
+        }
+        true
+    }
+
+    /// draw the prompt, or the place the run got, `offset` below the centre of the screen
+    pub fn draw(&self, ctx: &Context, offset: f32) {
+        if self.entry.is_some() {
+            draw_text_centred("New high score! Enter your name:", &ctx.font, offset);
+            draw_text_centred(format!("{}_", self.name).as_str(), &ctx.font, offset + 40.);
+        } else if let Some(place) = self.place {
+            draw_text_centred(format!("#{} in the high scores", place + 1).as_str(), &ctx.font, offset);
+        }
+    }
+}
+
+////////////////////////////////
+//
+// H I G H   S C O R E S
+//
+////////////////////////////////
+pub struct HighScoresScene {
+    highscores: HighScores,
+}
+
+impl HighScoresScene {
+    pub fn new() -> Self {
+        Self { highscores: HighScores::load() }
+    }
+}
+
+impl Scene for HighScoresScene {
+    fn update(&mut self, ctx: &mut Context) -> Transition {
+        if ctx.pressed(Action::Confirm) || ctx.pressed(Action::Back) {
+            return Transition::Pop;
+        }
+        Transition::None
+    }
+
+    fn draw(&self, ctx: &Context) {
+        clear_background(BLACK);
+        let line = screen_height() / 16.;
+        let top = -line * 6.5;
+        draw_text_centred("- High Scores -", &ctx.font, top);
+        if self.highscores.entries.is_empty() {
+            draw_text_centred("No runs yet", &ctx.font, 0.);
+        }
+        for (place, entry) in self.highscores.entries.iter().enumerate() {
+            let progress = match (entry.mode.as_str(), entry.won) {
+                ("endless", _) => format!("wave {}", entry.stages + 1),
+                (_, true) => "won".to_string(),
+                _ => format!("{} rooms", entry.stages),
+            };
+            draw_text_centred(format!("{:>2}. {:<12} {:>6}  {} kills, {}", place + 1, entry.name, entry.score, entry.kills, progress).as_str(), &ctx.font, top + line * (place + 1) as f32 * 1.1);
+        }
+        draw_text_centred(format!("Press {} to go back", ctx.settings.keybindings.hint(Action::Back)).as_str(), &ctx.font, top + line * 13.);
+    }
+}
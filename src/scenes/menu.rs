@@ -0,0 +1,140 @@
+use macroquad::prelude::*;
+use crate::{draw_text_centred, VERSION};
+use crate::audio::{MENU_MUSIC, MUSIC_FADE};
+use crate::savegame::SaveData;
+use crate::input::Action;
+use crate::scene::{Context, Scene, Transition};
+use crate::scenes::game::GameScene;
+use crate::scenes::highscores::HighScoresScene;
+use crate::scenes::settings::SettingsScene;
+
+//////////////////////////////
+//
+// Main Menu
+//
+//////////////////////////////
+pub struct MainMenu {
+    selected: usize,
+    items: Vec<&'static str>,
+}
+
+impl MainMenu {
+    pub fn new() -> Self {
+        let mut items = vec!["Start", "Endless", "High Scores", "Settings", "Quit Game"];
+        if SaveData::exists() {
+            items.insert(0, "Continue");
+        }
+        Self {
+            selected: 0,
+            items,
+        }
+    }
+}
+
+impl Scene for MainMenu {
+    fn enter(&mut self, ctx: &mut Context) {
+        ctx.audio.play_music(MENU_MUSIC, MUSIC_FADE);
+    }
+
+    fn resume(&mut self, ctx: &mut Context) {
+        ctx.audio.play_music(MENU_MUSIC, MUSIC_FADE);
+    }
+
+    fn update(&mut self, ctx: &mut Context) -> Transition {
+        // get inputs
+        let (confirm, up, down) = (ctx.pressed(Action::Confirm), ctx.pressed(Action::MenuUp), ctx.pressed(Action::MenuDown));
+        if confirm {
+            ctx.error = None;
+            match self.items[self.selected] {
+                "Continue" => {
+                    // a run is continued once, and one that can't be loaded never will be
+                    let continued = SaveData::load().is_some_and(|save| ctx.gs.continue_run(save));
+                    SaveData::delete();
+                    self.items.retain(|item| *item != "Continue");
+                    self.selected = 0;
+                    if continued {
+                        return Transition::Push(Box::new(GameScene::new()));
+                    }
+                    ctx.error = Some("Couldn't load the saved run".to_string());
+                }
+                "Start" => return Transition::Push(Box::new(GameScene::new())),
+                "Endless" => {
+                    // a new set of rooms every run
+                    if ctx.gs.start_endless(miniquad::date::now() as u64) {
+                        return Transition::Push(Box::new(GameScene::new()));
+                    }
+                    ctx.error = Some("Couldn't load the first room".to_string());
+                }
+                "High Scores" => return Transition::Push(Box::new(HighScoresScene::new())),
+                "Settings" => return Transition::Push(Box::new(SettingsScene::new())),
+                "Quit Game" => {
+                    println!("[INFO] Exiting...");
+                    return Transition::Quit;
+                }
+                _ => {}
+            }
+        }
+        if up {
+            if self.selected != 0 {
+                self.selected -= 1;
+            } else {
+                self.selected = self.items.len() - 1;
+            }
+        } else if down {
+            self.selected += 1;
+            if self.selected >= self.items.len() {
+                self.selected = 0
+            }
+        }
+        Transition::None
+    }
+
+    fn draw(&self, ctx: &Context) {
+        clear_background(BLACK);
+        // width of the title: 540
+        // height:              50
+
+        // draw menu title
+        if screen_width() > 3840. && screen_height() > 2160. {
+            draw_text_centred("Journey of the", &ctx.font, -500.);
+            draw_text_centred("Meadow King", &ctx.font, -200.);
+        } else if screen_width() > 530. && screen_height() > 300. {
+            draw_texture_ex(ctx.gs.assets.get("menu::title").unwrap(), screen_width() / 2. - 270., 70., WHITE, DrawTextureParams {
+                dest_size: None,
+                ..Default::default()
+            });
+        } else {
+            draw_text_centred("Journey of the", &ctx.font, -50.);
+            draw_text_centred("Meadow King", &ctx.font, -25.);
+        }
+        // draw menu options
+        for i in 0..self.items.len() {
+            if i == self.selected {
+                draw_text_centred(format!("> {} <", self.items.get(i).unwrap()).as_str(), &ctx.font, 50. * i as f32);
+            } else {
+                draw_text_centred(self.items.get(i).unwrap(), &ctx.font, 50. * i as f32);
+            }
+        }
+        // what went wrong with the last pick
+        if let Some(error) = &ctx.error {
+            draw_text_centred(error, &ctx.font, 50. * self.items.len() as f32 + 25.);
+        }
+        // draw version
+        draw_text_ex(format!("Journey of the Meadow King v. {}", VERSION).as_str(), screen_width() - 11. * (30. + VERSION.len() as f32), screen_height() - 40., TextParams {
+            font: Some(&ctx.font),
+            font_size: 20,
+            font_scale: 1.0,
+            font_scale_aspect: 1.0,
+            rotation: 0.0,
+            color: WHITE,
+        });
+        draw_text_ex(format!("Build {}", build_id::get()).as_str(), screen_width() - 11. * (6. + build_id::get().to_string().len() as f32), screen_height() - 20., TextParams {
+            font: Some(&ctx.font),
+            font_size: 20,
+            font_scale: 1.0,
+            font_scale_aspect: 1.0,
+            rotation: 0.0,
+            color: WHITE,
+        });
+    }
+}
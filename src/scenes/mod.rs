@@ -0,0 +1,9 @@
+pub mod menu;
+pub mod controls;
+pub mod game;
+pub mod highscores;
+pub mod pause;
+pub mod results;
+pub mod death;
+pub mod settings;
+pub mod won;
@@ -0,0 +1,62 @@
+use macroquad::prelude::*;
+use crate::GAME_SCREEN_MAIN;
+use crate::savegame::SaveData;
+use crate::input::Action;
+use crate::scene::{Context, Scene, Transition};
+use crate::scenes::settings::SettingsScene;
+
+////////////////////////////////
+//
+// P A U S E   M E N U
+//
+////////////////////////////////
+pub struct PauseScene;
+
+impl Scene for PauseScene {
+    fn enter(&mut self, ctx: &mut Context) {
+        ctx.audio.set_ducked(true);
+    }
+
+    fn exit(&mut self, ctx: &mut Context) {
+        ctx.audio.set_ducked(false);
+    }
+
+    fn update(&mut self, ctx: &mut Context) -> Transition {
+        if ctx.pressed(Action::Back) || ctx.pressed(Action::Pause) {
+            Transition::Pop
+        } else if ctx.pressed(Action::OpenSettings) {
+            Transition::Push(Box::new(SettingsScene::new()))
+        } else if ctx.pressed(Action::SaveAndQuit) {
+            // continue from the main menu next time
+            SaveData::from_world(&ctx.gs.world).save();
+            println!("[INFO] Run saved");
+            Transition::Quit
+        } else {
+            Transition::None
+        }
+    }
+
+    fn draw(&self, ctx: &Context) {
+        ctx.gs.canvas.set_camera();
+        draw_rectangle(0., 0., GAME_SCREEN_MAIN.x, ctx.gs.canvas.height(), BLACK);
+        draw_texture(ctx.gs.assets.get("menu::paused").unwrap(), 2., 25., WHITE);
+        let keys = &ctx.settings.keybindings;
+        draw_text_ex(format!("[{}] Settings", keys.hint(Action::OpenSettings)).as_str(), 2., 115., TextParams {
+            font: Some(&ctx.font),
+            font_size: 10,
+            color: WHITE,
+            ..Default::default()
+        });
+        draw_text_ex(format!("[{}] Save & Quit", keys.hint(Action::SaveAndQuit)).as_str(), 2., 127., TextParams {
+            font: Some(&ctx.font),
+            font_size: 10,
+            color: WHITE,
+            ..Default::default()
+        });
+        ctx.gs.canvas.draw_to_screen();
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,59 @@
+use macroquad::prelude::*;
+use crate::draw_text_centred;
+use crate::GameState;
+use crate::audio::{DEATH_MUSIC, MUSIC_FADE};
+use crate::input::Action;
+use crate::scene::{Context, Scene, Transition};
+use crate::scenes::highscores::NameEntry;
+use crate::world::World;
+
+////////////////////////////////
+//
+// R E S U L T S
+//
+////////////////////////////////
+/// the end of an endless run
+pub struct ResultsScene {
+    waves: i32,
+    kills: i32,
+    score: i32,
+    name_entry: NameEntry,
+}
+
+impl ResultsScene {
+    pub fn new(world: &World) -> Self {
+        Self {
+            waves: world.endless.as_ref().map(|endless| endless.wave).unwrap_or(0),
+            kills: world.enemies_killcount,
+            score: world.score,
+            name_entry: NameEntry::new(world, false),
+        }
+    }
+}
+
+impl Scene for ResultsScene {
+    fn enter(&mut self, ctx: &mut Context) {
+        ctx.audio.play_music(DEATH_MUSIC, MUSIC_FADE);
+    }
+
+    fn update(&mut self, ctx: &mut Context) -> Transition {
+        if self.name_entry.update() {
+            return Transition::None;
+        }
+        if ctx.pressed(Action::Confirm) {
+            ctx.gs = GameState::new();
+            return Transition::Pop;
+        }
+        Transition::None
+    }
+
+    fn draw(&self, ctx: &Context) {
+        clear_background(BLACK);
+        draw_text_centred("- Game Over -", &ctx.font, -150.);
+        draw_text_centred(format!("Waves survived: {}", self.waves).as_str(), &ctx.font, -75.);
+        draw_text_centred(format!("Enemies killed: {}", self.kills).as_str(), &ctx.font, -25.);
+        draw_text_centred(format!("Score: {}", self.score).as_str(), &ctx.font, 25.);
+        draw_text_centred(format!("Press {} to go to main menu", ctx.settings.keybindings.hint(Action::Confirm)).as_str(), &ctx.font, 75.);
+        self.name_entry.draw(ctx, 130.);
+    }
+}
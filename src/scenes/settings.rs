@@ -0,0 +1,128 @@
+use macroquad::prelude::*;
+use crate::{draw_text_centred, DebugSettings};
+use crate::input::Action;
+use crate::scene::{Context, Scene, Transition};
+use crate::scenes::controls::ControlsScene;
+use crate::settings::MAX_WINDOW_SCALE;
+
+const ITEMS: [&str; 12] = ["Volume", "Music Volume", "SFX Volume", "Fullscreen", "Window Scale", "Screen Shake", "Show FPS", "Aim", "Mouse Aim", "Controls", "Debug Mode", "Back"];
+
+/// one step of a volume slider, in tenths
+fn step_volume(volume: f32, step: i32) -> f32 {
+    ((volume * 10.).round() + step as f32).clamp(0., 10.) / 10.
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value { "On" } else { "Off" }
+}
+
+////////////////////////////////
+//
+// S E T T I N G S
+//
+////////////////////////////////
+pub struct SettingsScene {
+    selected: usize,
+}
+
+impl SettingsScene {
+    pub fn new() -> Self {
+        Self { selected: 0 }
+    }
+
+    /// change the selected setting one step, `step` is -1 for left and 1 for right or enter
+    fn change(&self, ctx: &mut Context, step: i32) {
+        let settings = &mut ctx.settings;
+        match ITEMS[self.selected] {
+            "Volume" => settings.volume = step_volume(settings.volume, step),
+            "Music Volume" => settings.music_volume = step_volume(settings.music_volume, step),
+            "SFX Volume" => settings.sfx_volume = step_volume(settings.sfx_volume, step),
+            "Fullscreen" => {
+                settings.fullscreen = !settings.fullscreen;
+                settings.apply_window();
+            }
+            "Window Scale" => {
+                settings.window_scale = (settings.window_scale + step).clamp(1, MAX_WINDOW_SCALE);
+                settings.apply_window();
+            }
+            "Screen Shake" => settings.screen_shake = !settings.screen_shake,
+            "Show FPS" => settings.show_fps = !settings.show_fps,
+            "Aim" => settings.analog_aim = !settings.analog_aim,
+            "Mouse Aim" => settings.mouse_aim = !settings.mouse_aim,
+            "Debug Mode" => {
+                ctx.debug_settings.debug_mode = !ctx.debug_settings.debug_mode;
+                if !ctx.debug_settings.debug_mode {
+                    ctx.debug_settings = DebugSettings::new();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// the text of a menu item with its current value
+    fn label(&self, ctx: &Context, item: &str) -> String {
+        let settings = &ctx.settings;
+        match item {
+            "Volume" => format!("Volume: {}%", (settings.volume * 100.).round()),
+            "Music Volume" => format!("Music Volume: {}%", (settings.music_volume * 100.).round()),
+            "SFX Volume" => format!("SFX Volume: {}%", (settings.sfx_volume * 100.).round()),
+            "Fullscreen" => format!("Fullscreen: {}", on_off(settings.fullscreen)),
+            "Window Scale" => format!("Window Scale: {}x", settings.window_scale),
+            "Screen Shake" => format!("Screen Shake: {}", on_off(settings.screen_shake)),
+            "Show FPS" => format!("Show FPS: {}", on_off(settings.show_fps)),
+            "Aim" => format!("Aim: {}", if settings.analog_aim { "360" } else { "8 Directions" }),
+            "Mouse Aim" => format!("Mouse Aim: {}", on_off(settings.mouse_aim)),
+            "Debug Mode" => format!("Debug Mode: {}", on_off(ctx.debug_settings.debug_mode)),
+            _ => item.to_string(),
+        }
+    }
+}
+
+impl Scene for SettingsScene {
+    fn update(&mut self, ctx: &mut Context) -> Transition {
+        let (confirm, back) = (ctx.pressed(Action::Confirm), ctx.pressed(Action::Back));
+        let (up, down, left, right) = (ctx.pressed(Action::MenuUp), ctx.pressed(Action::MenuDown), ctx.pressed(Action::MenuLeft), ctx.pressed(Action::MenuRight));
+        if back || (confirm && ITEMS[self.selected] == "Back") {
+            ctx.settings.save();
+            return Transition::Pop;
+        }
+        if confirm && ITEMS[self.selected] == "Controls" {
+            return Transition::Push(Box::new(ControlsScene::new()));
+        }
+        if is_key_pressed(KeyCode::F3) {
+            self.selected = ITEMS.iter().position(|item| *item == "Debug Mode").unwrap();
+            self.change(ctx, 1);
+        } else if left {
+            self.change(ctx, -1);
+        } else if right || confirm {
+            self.change(ctx, 1);
+        }
+        if up {
+            if self.selected != 0 {
+                self.selected -= 1;
+            } else {
+                self.selected = ITEMS.len() - 1;
+            }
+        } else if down {
+            self.selected += 1;
+            if self.selected >= ITEMS.len() {
+                self.selected = 0
+            }
+        }
+        Transition::None
+    }
+
+    fn draw(&self, ctx: &Context) {
+        clear_background(BLACK);
+        draw_text_centred("- Settings -", &ctx.font, -210.);
+        for (i, item) in ITEMS.iter().enumerate() {
+            let label = self.label(ctx, item);
+            if i == self.selected {
+                draw_text_centred(format!("< {} >", label).as_str(), &ctx.font, -160. + 28. * i as f32);
+            } else {
+                draw_text_centred(label.as_str(), &ctx.font, -160. + 28. * i as f32);
+            }
+        }
+        draw_text_centred(format!("Press [{}] to go back", ctx.settings.keybindings.hint(Action::Back)).as_str(), &ctx.font, 200.);
+    }
+}
@@ -0,0 +1,48 @@
+use macroquad::prelude::*;
+use crate::{draw_text_centred, GameState};
+use crate::audio::{MUSIC_FADE, VICTORY_MUSIC};
+use crate::input::Action;
+use crate::scene::{Context, Scene, Transition};
+use crate::scenes::highscores::NameEntry;
+use crate::world::World;
+
+////////////////////////////////
+//
+// Y O U ' V E   W O N
+//
+////////////////////////////////
+pub struct WonScene {
+    name_entry: NameEntry,
+    score: i32,
+}
+
+impl WonScene {
+    pub fn new(world: &World) -> Self {
+        Self { name_entry: NameEntry::new(world, true), score: world.score }
+    }
+}
+
+impl Scene for WonScene {
+    fn enter(&mut self, ctx: &mut Context) {
+        ctx.audio.play_music(VICTORY_MUSIC, MUSIC_FADE);
+    }
+
+    fn update(&mut self, ctx: &mut Context) -> Transition {
+        if self.name_entry.update() {
+            return Transition::None;
+        }
+        if ctx.pressed(Action::Confirm) {
+            ctx.gs = GameState::new();
+            return Transition::Pop;
+        }
+        Transition::None
+    }
+
+    fn draw(&self, ctx: &Context) {
+        clear_background(BLACK);
+        draw_text_centred("You've won!", &ctx.font, -50.);
+        draw_text_centred(format!("Score: {}", self.score).as_str(), &ctx.font, 0.);
+        draw_text_centred(format!("Press {} to go to main menu", ctx.settings.keybindings.hint(Action::Confirm)).as_str(), &ctx.font, 50.);
+        self.name_entry.draw(ctx, 120.);
+    }
+}
@@ -0,0 +1,121 @@
+use macroquad::prelude::*;
+use nanoserde::{DeJson, SerJson};
+use crate::CANVAS_SIZE;
+use crate::input::Keybindings;
+use crate::storage::{read_config, write_config};
+
+/// file in the config dir the settings are kept in
+pub const SETTINGS_FILE: &str = "settings.json";
+pub const MAX_WINDOW_SCALE: i32 = 4;
+
+fn default_volume() -> f32 {
+    0.8
+}
+fn default_full_volume() -> f32 {
+    1.
+}
+fn default_window_scale() -> i32 {
+    2
+}
+fn default_true() -> bool {
+    true
+}
+
+/// the player's settings. Missing fields get their defaults, so old settings files keep working
+#[derive(DeJson, SerJson, Clone, Debug)]
+pub struct Settings {
+    /// master volume, 0 to 1
+    #[nserde(default_with = "default_volume")]
+    pub volume: f32,
+    /// 0 to 1, relative to `volume`
+    #[nserde(default_with = "default_full_volume")]
+    pub music_volume: f32,
+    /// 0 to 1, relative to `volume`
+    #[nserde(default_with = "default_full_volume")]
+    pub sfx_volume: f32,
+    #[nserde(default)]
+    pub fullscreen: bool,
+    /// the window is this many times the size of the canvas, 1 to `MAX_WINDOW_SCALE`
+    #[nserde(default_with = "default_window_scale")]
+    pub window_scale: i32,
+    #[nserde(default_with = "Keybindings::default")]
+    pub keybindings: Keybindings,
+    /// shake the room when the player gets hurt
+    #[nserde(default_with = "default_true")]
+    pub screen_shake: bool,
+    #[nserde(default)]
+    pub show_fps: bool,
+    /// the right stick of a gamepad and the mouse shoot at any angle instead of in the nearest of eight directions
+    #[nserde(default)]
+    pub analog_aim: bool,
+    /// holding the left mouse button shoots towards the cursor
+    #[nserde(default_with = "default_true")]
+    pub mouse_aim: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            volume: default_volume(),
+            music_volume: default_full_volume(),
+            sfx_volume: default_full_volume(),
+            fullscreen: false,
+            window_scale: default_window_scale(),
+            keybindings: Keybindings::default(),
+            screen_shake: true,
+            show_fps: false,
+            analog_aim: false,
+            mouse_aim: true,
+        }
+    }
+}
+
+impl Settings {
+    /// read the settings from the config dir. The defaults if there aren't any yet or they're broken
+    pub fn load() -> Self {
+        let Some(json) = read_config(SETTINGS_FILE) else {
+            return Self::default();
+        };
+        match Settings::deserialize_json(&json) {
+            Ok(mut settings) => {
+                settings.window_scale = settings.window_scale.clamp(1, MAX_WINDOW_SCALE);
+                settings.volume = settings.volume.clamp(0., 1.);
+                settings.music_volume = settings.music_volume.clamp(0., 1.);
+                settings.sfx_volume = settings.sfx_volume.clamp(0., 1.);
+                settings
+            }
+            Err(err) => {
+                println!("WARN: couldn't parse {}: {}. Using the default settings", SETTINGS_FILE, err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        write_config(SETTINGS_FILE, &self.serialize_json());
+    }
+
+    /// how loud sound effects play, master and effect volume together
+    pub fn effective_sfx_volume(&self) -> f32 {
+        self.volume * self.sfx_volume
+    }
+
+    /// how loud music plays, master and music volume together
+    pub fn effective_music_volume(&self) -> f32 {
+        self.volume * self.music_volume
+    }
+
+    /// size of the window in windowed mode
+    pub fn window_size(&self) -> Vec2 {
+        CANVAS_SIZE * self.window_scale as f32
+    }
+
+    /// resize the window and switch fullscreen on or off
+    pub fn apply_window(&self) {
+        set_fullscreen(self.fullscreen);
+        if !self.fullscreen {
+            let size = self.window_size();
+            request_new_screen_size(size.x, size.y);
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use macroquad::prelude::*;
+use nanoserde::DeJson;
+use crate::STD_TIMER_MAX;
+use crate::assets::asset_path;
+use crate::audio::STAGE_MUSIC;
+use crate::generator::{generate, map_path};
+use crate::tiled::TiledMap;
+
+/// file the stage sequence is read from. If it doesn't exist, the built-in campaign is used
+pub const LEVELS_PATH: &str = "levels.json";
+
+/// maps that are compiled in, used when a map file of the level list can't be read
+const BUILTIN_MAPS: [(&str, &str); 6] = [
+    ("map.json", include_str!("../assets/map.json")),
+    ("map2.json", include_str!("../assets/map2.json")),
+    ("map3.json", include_str!("../assets/map3.json")),
+    ("map4.json", include_str!("../assets/map4.json")),
+    ("map5.json", include_str!("../assets/map5.json")),
+    ("map6.json", include_str!("../assets/map6.json")),
+];
+
+/// the edge of the room the player leaves it through
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Exit {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Exit {
+    pub const ALL: [Exit; 4] = [Exit::North, Exit::East, Exit::South, Exit::West];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "north" => Some(Exit::North),
+            "east" => Some(Exit::East),
+            "south" => Some(Exit::South),
+            "west" => Some(Exit::West),
+            _ => None,
+        }
+    }
+    /// the name `from_name` reads
+    pub fn name(&self) -> &'static str {
+        match self {
+            Exit::North => "north",
+            Exit::East => "east",
+            Exit::South => "south",
+            Exit::West => "west",
+        }
+    }
+    /// unit vector pointing out of the room
+    pub fn direction(&self) -> Vec2 {
+        match self {
+            Exit::North => vec2(0., -1.),
+            Exit::East => vec2(1., 0.),
+            Exit::South => vec2(0., 1.),
+            Exit::West => vec2(-1., 0.),
+        }
+    }
+}
+
+/// where an exit of a stage leads
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Destination {
+    /// index of the stage in the stage list
+    Stage(usize),
+    /// the run is won
+    End,
+}
+
+/// one entry of the level list as it's written in `LEVELS_PATH`
+#[derive(DeJson)]
+pub struct StageEntry {
+    /// name other stages' `exits` use for this one. Defaults to the map path
+    #[nserde(default)]
+    pub id: String,
+    /// path of the Tiled JSON file, relative to `ASSETS_DIR`
+    #[nserde(default)]
+    pub map: String,
+    /// without a `map`, the stage gets the room `generator::generate` makes from this seed
+    pub seed: Option<u64>,
+    /// ticks until the exit opens. 0 means `STD_TIMER_MAX`
+    #[nserde(default)]
+    pub timer: i32,
+    /// enemy spawn chance (1 in x per tick) over the course of the stage, interpolated linearly
+    #[nserde(default)]
+    pub spawn_rate: Vec<f32>,
+    /// names of the enemy kinds that spawn here. Empty means all of them
+    #[nserde(default)]
+    pub enemies: Vec<String>,
+    /// enemies that have to be killed before the exit opens
+    #[nserde(default)]
+    pub kill_goal: i32,
+    /// `north`, `east`, `south` or `west`, leading to the next stage of the list
+    #[nserde(default)]
+    pub exit: String,
+    /// edge -> id of the stage it leads to, or `end`. Replaces `exit` and lets runs branch
+    #[nserde(default)]
+    pub exits: HashMap<String, String>,
+    #[nserde(default)]
+    pub boss: bool,
+    /// music track of the stage, relative to `ASSETS_DIR`. Defaults to `STAGE_MUSIC`
+    #[nserde(default)]
+    pub music: String,
+}
+
+impl StageEntry {
+    /// where the map is loaded from, relative to `ASSETS_DIR`. Generated rooms get the path `jotmk-mapgen` writes them to
+    pub fn map_path(&self) -> String {
+        match self.seed {
+            Some(seed) if self.map.is_empty() => map_path(seed),
+            _ => self.map.clone(),
+        }
+    }
+
+    /// the name other stages' `exits` use for this one
+    pub fn id(&self) -> String {
+        if self.id.is_empty() { self.map_path() } else { self.id.clone() }
+    }
+
+    /// the edge `exit` names. Unknown or missing ones are south
+    pub fn exit(&self) -> Exit {
+        Exit::from_name(&self.exit).unwrap_or(Exit::South)
+    }
+
+    /// the edges the room can be left through: the known ones of `exits`, or `exit`
+    pub fn edges(&self) -> Vec<Exit> {
+        if self.exits.is_empty() {
+            vec![self.exit()]
+        } else {
+            Exit::ALL.into_iter().filter(|exit| self.exits.keys().any(|name| Exit::from_name(name) == Some(*exit))).collect()
+        }
+    }
+}
+
+/// the level list as it's written in `LEVELS_PATH`
+#[derive(DeJson)]
+pub struct Levels {
+    pub stages: Vec<StageEntry>,
+}
+
+/// one stage of the campaign
+#[derive(Clone)]
+pub struct Stage {
+    /// the room as Tiled JSON
+    pub map: String,
+    /// where the map was loaded from, relative to `ASSETS_DIR`
+    pub map_path: String,
+    pub timer: i32,
+    pub spawn_rate: Vec<f32>,
+    pub enemies: Vec<String>,
+    pub kill_goal: i32,
+    /// the edges the player can leave the room through and where they lead. Never empty
+    pub exits: Vec<(Exit, Destination)>,
+    /// on boss stages the exit opens when the boss dies instead of when the stage timer runs out
+    pub boss: bool,
+    /// multiply the health and speed of the enemies that spawn
+    pub enemy_health: f32,
+    pub enemy_speed: f32,
+    /// music track, relative to `ASSETS_DIR`. Empty means no music
+    pub music: String,
+}
+
+impl Stage {
+    /// where leaving through `exit` leads. Exits the stage doesn't list (e.g. an exit zone pointing
+    /// somewhere else) lead to where its first exit does
+    pub fn destination(&self, exit: Exit) -> Destination {
+        self.exits.iter().find(|(direction, _)| *direction == exit).unwrap_or(&self.exits[0]).1
+    }
+
+    /// the spawn chance (1 in x per tick) once `progress` (0 to 1) of the stage timer has passed
+    pub fn spawn_speed(&self, progress: f32) -> f32 {
+        match self.spawn_rate.len() {
+            0 => 69.,
+            1 => self.spawn_rate[0],
+            len => {
+                let position = progress.clamp(0., 1.) * (len - 1) as f32;
+                let index = (position as usize).min(len - 2);
+                let t = position - index as f32;
+                self.spawn_rate[index] + (self.spawn_rate[index + 1] - self.spawn_rate[index]) * t
+            }
+        }
+    }
+}
+
+/// read the map at `path`. In asset path mode the file in `ASSETS_DIR` comes first and the
+/// built-in map of the same name is the fallback, otherwise it's the other way round
+fn load_map_json(path: &str, live: bool) -> Option<String> {
+    let builtin = BUILTIN_MAPS.iter().find(|(name, _)| *name == path).map(|(_, json)| json.to_string());
+    if !live && builtin.is_some() {
+        return builtin;
+    }
+    match std::fs::read_to_string(asset_path(path)) {
+        Ok(json) => Some(json),
+        Err(err) => {
+            if builtin.is_none() {
+                println!("WARN: couldn't load map {}: {}", path, err);
+            }
+            builtin
+        }
+    }
+}
+
+/// load the stage sequence from `LEVELS_PATH`, falling back to the built-in campaign.
+/// `live` is whether maps come from `ASSETS_DIR` first, see `assets::live_assets`
+pub fn load_levels(live: bool) -> Vec<Stage> {
+    let builtin = include_str!("../levels.json");
+    let json = std::fs::read_to_string(LEVELS_PATH).unwrap_or_else(|_| builtin.to_string());
+    let levels = match Levels::deserialize_json(&json) {
+        Ok(levels) if !levels.stages.is_empty() => levels,
+        Ok(_) => {
+            println!("WARN: {} doesn't have any stages. Using the built-in levels", LEVELS_PATH);
+            Levels::deserialize_json(builtin).unwrap()
+        }
+        Err(err) => {
+            println!("WARN: couldn't parse {}: {}. Using the built-in levels", LEVELS_PATH, err);
+            Levels::deserialize_json(builtin).unwrap()
+        }
+    };
+
+    let stages = build_stages(levels, live);
+    if stages.is_empty() {
+        println!("WARN: none of the maps in {} could be loaded. Using the built-in levels", LEVELS_PATH);
+        return builtin_levels();
+    }
+    stages
+}
+
+/// the built-in campaign with the embedded maps
+pub fn builtin_levels() -> Vec<Stage> {
+    build_stages(Levels::deserialize_json(include_str!("../levels.json")).unwrap(), false)
+}
+
+/// load the maps of the level list and connect the stages
+fn build_stages(levels: Levels, live: bool) -> Vec<Stage> {
+    let mut stages = vec![];
+    // id and `exits` of every stage that was loaded, resolved once all of them are there
+    let mut links = vec![];
+    for entry in levels.stages {
+        let map = match entry.seed {
+            Some(seed) if entry.map.is_empty() => Some(generate(seed).to_tiled_json()),
+            _ => load_map_json(&entry.map, live),
+        };
+        let Some(map) = map else {
+            println!("WARN: skipping the stage with map {}", entry.map);
+            continue;
+        };
+        // a broken map would only fail once the player gets to it
+        let problems = match TiledMap::deserialize_json(&map) {
+            Ok(tiled) => tiled.problems(),
+            Err(err) => vec![format!("not a Tiled JSON map: {}", err)],
+        };
+        if !problems.is_empty() {
+            println!("WARN: skipping the stage with map {}: {}", entry.map, problems.join(", "));
+            continue;
+        }
+        if !entry.exit.is_empty() && Exit::from_name(&entry.exit).is_none() {
+            println!("WARN: stage {} has unknown exit `{}`, using south", entry.map, entry.exit);
+        }
+        let exit = entry.exit();
+        for name in entry.exits.keys() {
+            if Exit::from_name(name).is_none() {
+                println!("WARN: stage {} has unknown exit `{}`", entry.map, name);
+            }
+        }
+        let (id, map_path) = (entry.id(), entry.map_path());
+        links.push((id, exit, entry.exits));
+        stages.push(Stage {
+            map,
+            map_path,
+            timer: if entry.timer > 0 { entry.timer } else { STD_TIMER_MAX },
+            spawn_rate: entry.spawn_rate,
+            enemies: entry.enemies,
+            kill_goal: entry.kill_goal,
+            exits: vec![],
+            boss: entry.boss,
+            enemy_health: 1.,
+            enemy_speed: 1.,
+            music: if entry.music.is_empty() { STAGE_MUSIC.to_string() } else { entry.music },
+        });
+    }
+
+    // stages without `exits` lead to the next one in the list, the last one ends the run
+    let linear = |index: usize| if index + 1 < stages.len() { Destination::Stage(index + 1) } else { Destination::End };
+    let mut exits = vec![];
+    for (index, (_, exit, targets)) in links.iter().enumerate() {
+        let mut stage_exits = vec![];
+        for direction in Exit::ALL {
+            let Some(target) = targets.iter().find(|(name, _)| Exit::from_name(name) == Some(direction)).map(|(_, target)| target) else {
+                continue;
+            };
+            let destination = if target == "end" {
+                Destination::End
+            } else if let Some(next) = links.iter().position(|(id, _, _)| id == target) {
+                Destination::Stage(next)
+            } else {
+                println!("WARN: stage {} leads to unknown stage `{}`, using the next one", links[index].0, target);
+                linear(index)
+            };
+            stage_exits.push((direction, destination));
+        }
+        if stage_exits.is_empty() {
+            stage_exits.push((*exit, linear(index)));
+        }
+        exits.push(stage_exits);
+    }
+    for (stage, exits) in stages.iter_mut().zip(exits) {
+        stage.exits = exits;
+    }
+    stages
+}
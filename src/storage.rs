@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+
+/// name of the game's directory in the platform data and config dirs
+const DATA_DIR_NAME: &str = "jotmk";
+
+fn env_dir(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from)
+}
+
+/// where the game keeps what has to survive a restart, like the high scores:
+/// `%APPDATA%\jotmk` on Windows, `~/Library/Application Support/jotmk` on macOS and
+/// `$XDG_DATA_HOME/jotmk` (or `~/.local/share/jotmk`) everywhere else. `None` if there's no home directory
+pub fn data_dir() -> Option<PathBuf> {
+    let base = if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_DATA_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".local").join("share")))
+    };
+    base.map(|base| base.join(DATA_DIR_NAME))
+}
+
+/// where the settings are kept: the data dir on Windows and macOS,
+/// `$XDG_CONFIG_HOME/jotmk` (or `~/.config/jotmk`) everywhere else
+pub fn config_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") || cfg!(target_os = "macos") {
+        return data_dir();
+    }
+    let base = env_dir("XDG_CONFIG_HOME").or_else(|| env_dir("HOME").map(|home| home.join(".config")));
+    base.map(|base| base.join(DATA_DIR_NAME))
+}
+
+fn read(dir: Option<PathBuf>, name: &str) -> Option<String> {
+    std::fs::read_to_string(dir?.join(name)).ok()
+}
+
+fn write(dir: Option<PathBuf>, name: &str, contents: &str) {
+    let Some(dir) = dir else {
+        println!("WARN: no home directory, couldn't save {}", name);
+        return;
+    };
+    if let Err(err) = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(dir.join(name), contents)) {
+        println!("WARN: couldn't save {}: {}", dir.join(name).display(), err);
+    }
+}
+
+/// read the file called `name` in the data dir. `None` if it doesn't exist (yet)
+pub fn read_data(name: &str) -> Option<String> {
+    read(data_dir(), name)
+}
+
+/// write the file called `name` in the data dir, creating the dir if needed
+pub fn write_data(name: &str, contents: &str) {
+    write(data_dir(), name, contents);
+}
+
+/// delete the file called `name` in the data dir, if it's there
+pub fn remove_data(name: &str) {
+    if let Some(dir) = data_dir() {
+        let _ = std::fs::remove_file(dir.join(name));
+    }
+}
+
+/// read the file called `name` in the config dir. `None` if it doesn't exist (yet)
+pub fn read_config(name: &str) -> Option<String> {
+    read(config_dir(), name)
+}
+
+/// write the file called `name` in the config dir, creating the dir if needed
+pub fn write_config(name: &str, contents: &str) {
+    write(config_dir(), name, contents);
+}
@@ -0,0 +1,185 @@
+use macroquad::prelude::*;
+use crate::TILE_SIZE;
+use crate::collision::CollisionType;
+use crate::tiled::{property, TiledMap, TiledProperty};
+
+/// the tile layers that can have tiles with properties, bottom to top
+const LAYERS: [&str; 3] = ["background", "main", "objects"];
+
+/// what a tile does to whatever is on or in it. Set with tile properties in the Tiled tileset:
+/// `blocks_walk`, `blocks_bullets`, `slow` (speed multiplier), `damage` and `destructible` (bullet hits it takes)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TileInfo {
+    pub blocks_walk: bool,
+    pub blocks_bullets: bool,
+    /// multiplies the speed of the player and enemies walking over it
+    pub slow: f32,
+    /// hurts the player standing on it
+    pub damage: bool,
+    /// bullet hits left until the tile breaks. 0 if it can't be destroyed
+    pub hits_left: i32,
+}
+
+impl TileInfo {
+    pub const FLOOR: TileInfo = TileInfo { blocks_walk: false, blocks_bullets: false, slow: 1., damage: false, hits_left: 0 };
+
+    /// whether it blocks walking for good, i.e. it can't be shot away
+    pub fn is_wall(&self) -> bool {
+        self.blocks_walk && self.hits_left == 0
+    }
+
+    fn apply(&mut self, properties: &[TiledProperty]) {
+        if let Some(hits) = property::<i32>(properties, "destructible").filter(|hits| *hits > 0) {
+            self.hits_left = hits;
+            self.blocks_walk = true;
+            self.blocks_bullets = true;
+        }
+        if let Some(blocks_walk) = property(properties, "blocks_walk") {
+            self.blocks_walk = blocks_walk;
+        }
+        if let Some(blocks_bullets) = property(properties, "blocks_bullets") {
+            self.blocks_bullets = blocks_bullets;
+        }
+        if let Some(slow) = property::<f32>(properties, "slow") {
+            self.slow = self.slow.min(slow);
+        }
+        // `true`, or any amount of damage
+        if property(properties, "damage").unwrap_or(false) || property::<f32>(properties, "damage").is_some_and(|damage| damage > 0.) {
+            self.damage = true;
+        }
+    }
+}
+
+/// what every tile of the room does, stacked from all tile layers
+#[derive(Clone)]
+pub struct Terrain {
+    width: u32,
+    height: u32,
+    tiles: Vec<TileInfo>,
+    /// for destructible tiles: the layer the tile is in and what's left of the stack once it breaks
+    rubble: Vec<Option<(&'static str, TileInfo)>>,
+}
+
+impl Terrain {
+    /// a room without anything in it
+    pub fn new() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            tiles: vec![],
+            rubble: vec![],
+        }
+    }
+
+    /// read the tile layers and the tile properties of a map.
+    /// Tiles in the `main` layer block everything unless their properties say otherwise
+    pub fn from_tiled(map: &TiledMap) -> Self {
+        let size = (map.width * map.height) as usize;
+        let destructible = |name: &str, index: usize| map.layer(name).and_then(|layer| layer.data.get(index))
+            .is_some_and(|gid| property::<i32>(map.tile_properties(*gid), "destructible").is_some_and(|hits| hits > 0));
+        let rubble = (0..size).map(|index| {
+            let layer = LAYERS.into_iter().rev().find(|name| destructible(name, index))?;
+            Some((layer, Self::stack(map, index, |name| !destructible(name, index))))
+        }).collect();
+        Self {
+            width: map.width,
+            height: map.height,
+            tiles: (0..size).map(|index| Self::stack(map, index, |_| true)).collect(),
+            rubble,
+        }
+    }
+
+    /// the tile at `index`, stacked from the layers `include` lets through
+    fn stack(map: &TiledMap, index: usize, include: impl Fn(&str) -> bool) -> TileInfo {
+        let mut tile = TileInfo::FLOOR;
+        for name in LAYERS.into_iter().filter(|name| include(name)) {
+            let Some(&gid) = map.layer(name).and_then(|layer| layer.data.get(index)) else {
+                continue;
+            };
+            if gid == 0 {
+                continue;
+            }
+            if name == "main" {
+                tile.blocks_walk = true;
+                tile.blocks_bullets = true;
+            }
+            tile.apply(map.tile_properties(gid));
+        }
+        tile
+    }
+
+    fn index(&self, coords: Vec2) -> Option<usize> {
+        if coords.x < 0. || coords.y < 0. {
+            return None;
+        }
+        let (x, y) = ((coords.x / TILE_SIZE) as u32, (coords.y / TILE_SIZE) as u32);
+        if x < self.width && y < self.height {
+            Some((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// the tile in column `x` and row `y`. Outside of the room there's only floor
+    pub fn tile(&self, x: u32, y: u32) -> TileInfo {
+        if x < self.width && y < self.height {
+            self.tiles[(y * self.width + x) as usize]
+        } else {
+            TileInfo::FLOOR
+        }
+    }
+
+    /// the tile at `coords` (in room coordinates). Outside of the room there's only floor
+    pub fn at(&self, coords: Vec2) -> TileInfo {
+        self.index(coords).map(|index| self.tiles[index]).unwrap_or(TileInfo::FLOOR)
+    }
+
+    pub fn blocks_bullets(&self, coords: Vec2) -> bool {
+        self.at(coords).blocks_bullets
+    }
+
+    /// speed multiplier at `coords`
+    pub fn slow_at(&self, coords: Vec2) -> f32 {
+        self.at(coords).slow
+    }
+
+    /// the walls for the player, enemies and the flow field
+    pub fn collision_map(&self) -> Vec<CollisionType> {
+        let mut collision_map = vec![];
+        for y in 0..self.height {
+            for x in 0..self.width {
+                collision_map.push(if self.tiles[(y * self.width + x) as usize].blocks_walk {
+                    CollisionType::Solid(x, y)
+                } else {
+                    CollisionType::Empty(x, y)
+                });
+            }
+        }
+        collision_map
+    }
+
+    /// a bullet hit the tile at `coords`. Returns the tile and its layer if that broke it;
+    /// from now on it's whatever the other layers have there
+    pub fn hit(&mut self, coords: Vec2) -> Option<(u32, u32, &'static str)> {
+        let index = self.index(coords)?;
+        let tile = &mut self.tiles[index];
+        if tile.hits_left == 0 {
+            return None;
+        }
+        tile.hits_left -= 1;
+        if tile.hits_left > 0 {
+            return None;
+        }
+        let (layer, rest) = self.rubble[index].take().unwrap_or(("main", TileInfo::FLOOR));
+        *tile = rest;
+        Some((index as u32 % self.width, index as u32 / self.width, layer))
+    }
+}
@@ -0,0 +1,197 @@
+use std::str::{Chars, FromStr};
+use nanoserde::{DeJson, DeJsonErr, DeJsonState, DeJsonTok};
+
+// reads Tiled JSON maps for what `macroquad_tiled` doesn't hand out: the raw layers and the tile
+// properties. Also checks maps for problems and rewrites them into the dialect `load_map` accepts
+
+/// highest bits of a gid are the flip flags
+const GID_MASK: u32 = 0x1fff_ffff;
+/// tile layers the game draws, so every map needs them
+pub const DRAWN_LAYERS: [&str; 3] = ["background", "main", "objects"];
+
+#[derive(DeJson)]
+pub struct TiledMap {
+    pub width: u32,
+    pub height: u32,
+    pub layers: Vec<TiledLayer>,
+    pub tilesets: Vec<TiledTileset>,
+}
+
+#[derive(DeJson)]
+pub struct TiledLayer {
+    pub name: String,
+    /// `tilelayer` or `objectgroup`
+    #[nserde(rename = "type")]
+    pub kind: String,
+    #[nserde(default)]
+    pub data: Vec<u32>,
+    #[nserde(default)]
+    pub objects: Vec<TiledObject>,
+}
+
+#[derive(DeJson)]
+pub struct TiledObject {
+    pub name: String,
+    pub x: f32,
+    pub y: f32,
+    #[nserde(default)]
+    pub width: f32,
+    #[nserde(default)]
+    pub height: f32,
+    #[nserde(default)]
+    pub properties: Vec<TiledProperty>,
+}
+
+#[derive(DeJson)]
+pub struct TiledTileset {
+    #[nserde(default)]
+    pub firstgid: u32,
+    /// empty for external tilesets, which we don't support
+    #[nserde(default)]
+    pub image: String,
+    /// only the tiles that have properties set
+    #[nserde(default)]
+    pub tiles: Vec<TiledTile>,
+}
+
+#[derive(DeJson)]
+pub struct TiledTile {
+    pub id: u32,
+    #[nserde(default)]
+    pub properties: Vec<TiledProperty>,
+}
+
+#[derive(DeJson)]
+pub struct TiledProperty {
+    pub name: String,
+    pub value: PropertyValue,
+}
+
+/// a property value of any type, as text
+pub struct PropertyValue(pub String);
+
+impl DeJson for PropertyValue {
+    fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {
+        let value = match state.tok {
+            DeJsonTok::Str => state.as_string()?,
+            DeJsonTok::Bool(value) => value.to_string(),
+            DeJsonTok::U64(value) => value.to_string(),
+            DeJsonTok::I64(value) => value.to_string(),
+            DeJsonTok::F64(value) => value.to_string(),
+            _ => return Err(state.err_token("property value")),
+        };
+        state.next_tok(input)?;
+        Ok(PropertyValue(value))
+    }
+}
+
+impl TiledMap {
+    /// the tile layer called `name`
+    pub fn layer(&self, name: &str) -> Option<&TiledLayer> {
+        self.layers.iter().find(|layer| layer.name == name && layer.kind == "tilelayer")
+    }
+
+    /// every object of the object layer called `name`
+    pub fn objects<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a TiledObject> + 'a {
+        self.layers.iter().filter(move |layer| layer.name == name && layer.kind == "objectgroup").flat_map(|layer| &layer.objects)
+    }
+
+    /// everything that keeps the game from building or drawing the map's tilemap: missing drawn
+    /// layers, tilesets that aren't embedded, layers with the same name and layer types it can't use
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+        for name in DRAWN_LAYERS {
+            if self.layer(name).is_none() {
+                problems.push(format!("missing tile layer `{}`", name));
+            }
+        }
+        if self.tilesets.iter().any(|tileset| tileset.image.is_empty()) {
+            problems.push("tileset without an image (tilesets have to be embedded in the map)".to_string());
+        }
+        for (index, layer) in self.layers.iter().enumerate() {
+            if self.layers[..index].iter().any(|other| other.name == layer.name) {
+                problems.push(format!("more than one layer called `{}`", layer.name));
+            }
+            if layer.kind != "tilelayer" && layer.kind != "objectgroup" {
+                problems.push(format!("layer `{}` has type `{}`, only tile and object layers are supported", layer.name, layer.kind));
+            }
+        }
+        problems
+    }
+
+    /// the properties of the tile with the global id `gid`, as it's stored in the layers
+    pub fn tile_properties(&self, gid: u32) -> &[TiledProperty] {
+        let gid = gid & GID_MASK;
+        let Some(tileset) = self.tilesets.iter().filter(|tileset| tileset.firstgid <= gid).max_by_key(|tileset| tileset.firstgid) else {
+            return &[];
+        };
+        tileset.tiles.iter().find(|tile| tile.id == gid - tileset.firstgid).map(|tile| tile.properties.as_slice()).unwrap_or(&[])
+    }
+}
+
+/// the map JSON in the form `macroquad_tiled` reads: it only takes property values as strings and calls
+/// object layers `objectlayer`, while Tiled writes bools and numbers and `objectgroup`
+pub fn macroquad_tiled_json(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    // the last string read and, right after a `:`, the key the next value belongs to
+    let mut last_string = String::new();
+    let mut key: Option<String> = None;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                let mut string = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            string.push(c);
+                            string.extend(chars.next());
+                        }
+                        _ => string.push(c),
+                    }
+                }
+                if key.as_deref() == Some("type") && string == "objectgroup" {
+                    out.push_str("\"objectlayer\"");
+                } else {
+                    out.push('"');
+                    out.push_str(&string);
+                    out.push('"');
+                }
+                key = None;
+                last_string = string;
+            }
+            ':' => {
+                key = Some(std::mem::take(&mut last_string));
+                out.push(c);
+            }
+            // a bool or number property value
+            c if key.as_deref() == Some("value") && !c.is_whitespace() && c != '{' && c != '[' => {
+                out.push('"');
+                out.push(c);
+                while let Some(&c) = chars.peek() {
+                    if c == ',' || c == '}' || c == ']' || c.is_whitespace() {
+                        break;
+                    }
+                    out.push(c);
+                    chars.next();
+                }
+                out.push('"');
+                key = None;
+            }
+            c => {
+                if !c.is_whitespace() {
+                    key = None;
+                    last_string.clear();
+                }
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+/// the property called `name`, if it's set and has the right type
+pub fn property<T: FromStr>(properties: &[TiledProperty], name: &str) -> Option<T> {
+    properties.iter().find(|property| property.name == name).and_then(|property| property.value.0.parse().ok())
+}
@@ -0,0 +1,554 @@
+use macroquad::prelude::*;
+use macroquad::rand::{gen_range, srand};
+use crate::{GAME_SCREEN_MAIN, TILE_SIZE};
+use crate::boss::Boss;
+use crate::bullet::{Bullet, Faction, Impact};
+use crate::collision::{find_spawnpoints, CollisionType};
+use crate::endless::Endless;
+use crate::enemy::{Enemy, EnemyKinds};
+use crate::input::InputFrame;
+use crate::pathfinding::FlowField;
+use crate::items::{Item, ItemDrop, ITEM_DROP_CHANCE};
+use crate::objects::{find_objects, MapObjects, ScriptedSpawn};
+use crate::player::{apply_input, update_fixed, Player};
+use crate::stage::{Destination, Exit, Stage};
+use crate::terrain::Terrain;
+use crate::tiled::TiledMap;
+
+/// points for leaving a room through its exit
+const STAGE_CLEAR_BONUS: i32 = 50;
+/// extra points for leaving a room without getting hurt in it
+const NO_HIT_BONUS: i32 = 100;
+/// points for killing the boss
+const BOSS_KILL_BONUS: i32 = 50;
+/// winning the campaign faster than this (in ticks) gives a point per second left
+const PAR_TIME: i32 = 60 * 60 * 6;
+/// in an endless run, ticks survived per point
+const SURVIVAL_TICKS_PER_POINT: i32 = 60;
+
+/// something that happened during a tick, for the renderer (and anything else) to react to
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WorldEvent {
+    /// the player fired, with every bullet of a spread shot at once
+    PlayerShot,
+    /// the enemy with this index was hit but survived
+    EnemyHit(usize),
+    /// a bullet hit a wall
+    BulletImpact,
+    /// the destructible tile at this position broke and has to be removed from this layer of the tilemap
+    TileDestroyed(u32, u32, &'static str),
+    EnemyKilled,
+    BossHit,
+    BossDefeated,
+    ItemPickedUp,
+    ItemUsed,
+    PlayerHurt,
+    PlayerDied,
+    /// the player walked into the trigger with this index in `World::objects`
+    Triggered(usize),
+    /// the player left the room through this exit; the next stage has to be loaded with `World::load_stage`
+    StageCleared(Exit),
+    /// the player cleared the last stage
+    Won,
+}
+
+/// the gameplay simulation. Doesn't touch the GPU or the keyboard, so it can run headless
+pub struct World {
+    pub player: Player,
+    pub bullets: Vec<Bullet>,
+    /// impact effects of bullets that hit a wall
+    pub impacts: Vec<Impact>,
+    pub enemies: Vec<Enemy>,
+    /// the boss of the current stage, if it's a boss stage and the boss is still alive
+    pub boss: Option<Boss>,
+    /// items lying on the map
+    pub items: Vec<ItemDrop>,
+    pub enemies_killcount: i32,
+    /// enemies killed in the current stage, for its kill goal
+    pub stage_kills: i32,
+    /// kills, stage clear, no-hit and time bonuses
+    pub score: i32,
+    /// rooms left through an exit in this run. Runs can branch, so it's not `current_stage`
+    pub stages_cleared: i32,
+    /// whether the player got hurt in the current stage, which loses the no-hit bonus
+    pub stage_hurt: bool,
+    /// ticks since the run started
+    pub run_ticks: i32,
+    /// the random generator is seeded with this plus `stages_cleared` at the start of every stage,
+    /// so a saved run plays its stage the same way when it's continued
+    pub seed: u64,
+    pub enemy_kinds: EnemyKinds,
+    pub stages: Vec<Stage>,
+    /// index of the current stage in `stages`. Runs can branch, so it's not the number of rooms played
+    pub current_stage: usize,
+    pub stage_timer: i32,
+    pub shoot_cooldown: i32,
+    /// what the tiles of the room do
+    pub terrain: Terrain,
+    /// the tiles that block walking, from `terrain`
+    pub collision_map: Vec<CollisionType>,
+    pub spawnpoints: Vec<Vec2>,
+    /// the entities and triggers of the current map
+    pub objects: MapObjects,
+    /// ticks since the current stage started, for the scripted enemy spawns
+    pub stage_ticks: i32,
+    /// leads the enemies to the player, updated every tick
+    pub flow_field: FlowField,
+    /// multiplies the stage's enemy spawn chance. Lower means more enemies
+    pub spawn_multiplier: f32,
+    /// player bullets destroy enemy bullets they touch
+    pub bullets_cancel: bool,
+    pub invincibility: bool,
+    /// the endless run, if this is one. Its waves replace the stage list
+    pub endless: Option<Endless>,
+    /// events of the ticks since the last `drain_events`
+    pub events: Vec<WorldEvent>,
+}
+
+impl World {
+    pub fn new(stages: Vec<Stage>, enemy_kinds: EnemyKinds, seed: u64) -> Self {
+        let stage_timer = stages[0].timer;
+        Self {
+            player: Default::default(),
+            bullets: vec![],
+            impacts: vec![],
+            enemies: vec![],
+            boss: None,
+            items: vec![],
+            enemies_killcount: 0,
+            stage_kills: 0,
+            score: 0,
+            stages_cleared: 0,
+            stage_hurt: false,
+            run_ticks: 0,
+            seed,
+            enemy_kinds,
+            stages,
+            current_stage: 0,
+            stage_timer,
+            shoot_cooldown: 0,
+            terrain: Terrain::new(),
+            collision_map: vec![],
+            spawnpoints: vec![],
+            objects: MapObjects::default(),
+            stage_ticks: 0,
+            flow_field: FlowField::new(),
+            spawn_multiplier: 1.,
+            bullets_cancel: true,
+            invincibility: false,
+            endless: None,
+            events: vec![],
+        }
+    }
+
+    /// a world for an endless run with the rooms of `seed`
+    pub fn new_endless(seed: u64, enemy_kinds: EnemyKinds) -> Self {
+        let endless = Endless::new(seed);
+        let mut world = Self::new(vec![endless.stage(&enemy_kinds)], enemy_kinds, seed);
+        world.endless = Some(endless);
+        world
+    }
+
+    /// set up the room of the current stage from its `map`: put the player at the start and place the map's items
+    pub fn load_stage(&mut self, map: &TiledMap) {
+        srand(self.seed.wrapping_add(self.stages_cleared as u64));
+        self.reload_map(map);
+        self.player.reset_coords(self.player_start());
+        self.items.extend(self.objects.items.iter().map(|(item, coords)| ItemDrop::placed(*item, *coords)));
+        self.stage_ticks = 0;
+        self.boss = if self.stage().boss { Some(Boss::new()) } else { None };
+    }
+
+    /// swap in the terrain, spawnpoints and objects of a changed map, keeping everything else in the room
+    pub fn reload_map(&mut self, map: &TiledMap) {
+        self.terrain = Terrain::from_tiled(map);
+        self.collision_map = self.terrain.collision_map();
+        self.flow_field = FlowField::from_collision_map(&self.collision_map);
+        self.spawnpoints = find_spawnpoints(map);
+        self.objects = find_objects(map);
+    }
+
+    /// where the player (re)starts in the current room
+    pub fn player_start(&self) -> Vec2 {
+        self.objects.player_start.unwrap_or(Player::default_start())
+    }
+
+    pub fn stage(&self) -> &Stage {
+        &self.stages[self.current_stage]
+    }
+
+    /// the exit opens when the stage timer runs out and the kill goal is reached, or on boss stages when the boss is dead
+    pub fn exit_open(&self) -> bool {
+        if self.stage().boss {
+            self.boss.is_none()
+        } else {
+            self.stage_timer <= 0 && self.stage_kills >= self.stage().kill_goal
+        }
+    }
+
+    /// the exit the player stands in: one of the map's exit zones, or if it doesn't have any, one of the stage's edges
+    fn at_exit(&self) -> Option<Exit> {
+        let coords = self.player.coords;
+        if !self.objects.exits.is_empty() {
+            let player_hitbox = Rect::new(coords.x, coords.y, self.player.wh.x, self.player.wh.y);
+            return self.objects.exits.iter().find(|exit| exit.rect.overlaps(&player_hitbox)).map(|exit| exit.direction);
+        }
+        self.stage().exits.iter().map(|(exit, _)| *exit).find(|exit| match exit {
+            Exit::North => coords.y.round() <= 0.,
+            Exit::East => coords.x.round() >= GAME_SCREEN_MAIN.w.round() - 16.,
+            Exit::South => coords.y.round() >= (GAME_SCREEN_MAIN.y + GAME_SCREEN_MAIN.h).round() - 16.,
+            Exit::West => coords.x.round() <= 0.,
+        })
+    }
+
+    /// spawn the map's scripted enemies that are due and fire the triggers the player walked into
+    fn update_objects(&mut self) {
+        let due: Vec<ScriptedSpawn> = self.objects.enemy_spawns.iter().filter(|spawn| spawn.delay == self.stage_ticks).cloned().collect();
+        for spawn in due {
+            match self.enemy_kinds.find(&spawn.kind) {
+                Some(kind) => {
+                    for _ in 0..spawn.count {
+                        self.spawn_enemy(Enemy::new(kind, spawn.coords, &self.enemy_kinds));
+                    }
+                }
+                None => println!("WARN: scripted spawn of unknown enemy `{}`", spawn.kind),
+            }
+        }
+        self.stage_ticks += 1;
+
+        let player_hitbox = Rect::new(self.player.coords.x, self.player.coords.y, self.player.wh.x, self.player.wh.y);
+        for index in 0..self.objects.triggers.len() {
+            let trigger = &mut self.objects.triggers[index];
+            let inside = trigger.rect.overlaps(&player_hitbox);
+            let fire = inside && !trigger.inside && (!trigger.fired || trigger.repeat);
+            trigger.inside = inside;
+            if !fire {
+                continue;
+            }
+            trigger.fired = true;
+            // `spawn`: call `count` enemies of that kind to the spawnpoints
+            if let Some(name) = trigger.properties.get("spawn") {
+                let count = trigger.properties.get("count").and_then(|count| count.parse().ok()).unwrap_or(1);
+                match self.enemy_kinds.find(name) {
+                    Some(kind) if !self.spawnpoints.is_empty() => {
+                        for _ in 0..count {
+                            let coords = self.spawnpoints[gen_range(0, self.spawnpoints.len())];
+                            self.spawn_enemy(Enemy::new(kind, coords, &self.enemy_kinds));
+                        }
+                    }
+                    Some(_) => {}
+                    None => println!("WARN: trigger spawns unknown enemy `{}`", name),
+                }
+            }
+            self.events.push(WorldEvent::Triggered(index));
+        }
+    }
+
+    /// whether `enemy` can be put where it is: inside the room and not in a wall
+    pub fn can_spawn(&self, enemy: &Enemy) -> bool {
+        let (min, max) = (enemy.coords, enemy.coords + enemy.wh);
+        min.x >= 0. && min.y >= 0. && max.x <= GAME_SCREEN_MAIN.w && max.y <= GAME_SCREEN_MAIN.h && !enemy.collides(&self.collision_map)
+    }
+
+    /// add an enemy, made as tough as the stage asks for. Enemies that would be stuck in a wall are left out
+    fn spawn_enemy(&mut self, enemy: Enemy) {
+        if !self.can_spawn(&enemy) {
+            return;
+        }
+        let stage = self.stage();
+        let enemy = enemy.scaled(stage.enemy_health, stage.enemy_speed);
+        self.enemies.push(enemy);
+    }
+
+    /// remove every pair of a player bullet and an enemy bullet that touch
+    fn cancel_bullets(&mut self) {
+        let mut cancelled = vec![false; self.bullets.len()];
+        for i in 0..self.bullets.len() {
+            if self.bullets[i].faction != Faction::Player || cancelled[i] {
+                continue;
+            }
+            for j in 0..self.bullets.len() {
+                if self.bullets[j].faction == Faction::Enemy && !cancelled[j] && self.bullets[i].coords.distance(self.bullets[j].coords) < 4. {
+                    cancelled[i] = true;
+                    cancelled[j] = true;
+                    break;
+                }
+            }
+        }
+        let mut index = 0;
+        self.bullets.retain(|_| {
+            index += 1;
+            !cancelled[index - 1]
+        });
+    }
+
+    /// the player got hit: lose a life and restart the room
+    fn hurt_player(&mut self) {
+        self.player.health -= 1;
+        self.stage_hurt = true;
+        self.stage_timer += 50;
+        self.enemies.clear();
+        self.bullets.retain(|bullet| bullet.faction != Faction::Enemy);
+        self.player.reset_coords(self.player_start());
+        if let Some(boss) = &mut self.boss {
+            boss.reset_coords();
+        }
+        self.events.push(WorldEvent::PlayerHurt);
+    }
+
+    /// use the held item: run its `on_use` and, if it lasts, make it the active effect
+    pub fn use_held_effect(&mut self) {
+        if let Some(item) = self.player.held_effect.take() {
+            if item.duration() > 0 {
+                // the new effect replaces a running smoke bomb
+                self.player.decoy = None;
+            }
+            (item.info().on_use)(self);
+            if item.duration() > 0 {
+                self.player.effect = Some(item);
+                self.player.effect_duration = item.duration();
+            }
+            self.events.push(WorldEvent::ItemUsed);
+        }
+    }
+
+    /// count a killed enemy
+    pub fn on_enemy_killed(&mut self, enemy: &Enemy) {
+        self.enemies_killcount += 1;
+        self.stage_kills += 1;
+        self.score += self.enemy_kinds.kinds[enemy.kind].score;
+        self.events.push(WorldEvent::EnemyKilled);
+    }
+
+    pub fn drain_events(&mut self) -> Vec<WorldEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// advance the simulation by one fixed tick
+    pub fn step(&mut self, input: InputFrame) {
+        self.run_ticks += 1;
+        // endless runs score for every second survived
+        if self.endless.is_some() && self.run_ticks % SURVIVAL_TICKS_PER_POINT == 0 {
+            self.score += 1;
+        }
+        // update room timer. Boss stages don't have one, the boss calls its own minions
+        if self.stage_timer > 0 && !self.stage().boss {
+            self.stage_timer -= 1;
+            // spawn enemy if we're still gaming
+            let stage = self.stage();
+            let spawn_speed = stage.spawn_speed(1. - self.stage_timer as f32 / stage.timer as f32) * self.spawn_multiplier;
+            if !self.spawnpoints.is_empty() && gen_range(0, (spawn_speed as i32).max(1)) == 0 {
+                let enemy = Enemy::new_random(&self.spawnpoints, &stage.enemies, &self.enemy_kinds);
+                self.spawn_enemy(enemy);
+            }
+        }
+
+
+        self.update_objects();
+
+        // player update
+        update_fixed(&mut self.player);
+        // player input
+        apply_input(self, &input);
+        if input.use_item {
+            self.use_held_effect();
+        }
+
+        // decrease shoot cooldown
+        if self.shoot_cooldown > 0 {
+            self.shoot_cooldown -= 1;
+        }
+
+
+        // update bullets & enemies fixed
+        let terrain = &mut self.terrain;
+        let impacts = &mut self.impacts;
+        let events = &mut self.events;
+        let mut tile_destroyed = false;
+        self.bullets.retain_mut(|bullet| {
+            if bullet.update(terrain) {
+                impacts.push(Impact::new(bullet.coords));
+                events.push(WorldEvent::BulletImpact);
+                if let Some((x, y, layer)) = terrain.hit(bullet.coords) {
+                    events.push(WorldEvent::TileDestroyed(x, y, layer));
+                    tile_destroyed = true;
+                }
+                return false;
+            }
+            true
+        });
+        // broken tiles don't block anything anymore
+        if tile_destroyed {
+            self.collision_map = self.terrain.collision_map();
+            self.flow_field = FlowField::from_collision_map(&self.collision_map);
+        }
+        for impact in &mut self.impacts {
+            impact.update();
+        }
+        self.impacts.retain(|impact| impact.timer > 0);
+        self.bullets.retain(|bullet| !(bullet.coords.x > GAME_SCREEN_MAIN.w || bullet.coords.y > GAME_SCREEN_MAIN.h || bullet.coords.x < 0. || bullet.coords.y < 0.));
+        if self.bullets_cancel {
+            self.cancel_bullets();
+        }
+
+        self.flow_field.update(self.player.target() + self.player.wh / 2.);
+        let player_hitbox = Rect::new(self.player.coords.x, self.player.coords.y, self.player.wh.x, self.player.wh.y);
+        let vulnerable = !self.invincibility && !self.player.invincible; /*only if the player isn't invincible*/
+        let mut player_hit = false;
+        for enemy in &mut self.enemies {
+            if let Some(bullet) = enemy.update(&self.player, &self.collision_map, &self.terrain, &self.flow_field) {
+                self.bullets.push(bullet);
+            }
+            // check for player & enemy collision
+            if player_hitbox.overlaps(&Rect::new(enemy.coords.x, enemy.coords.y, enemy.wh.x, enemy.wh.y)) && vulnerable {
+                player_hit = true;
+                break;
+            }
+        }
+        if let Some(mut boss) = self.boss.take() {
+            boss.update(self);
+            if player_hitbox.overlaps(&boss.hitbox()) && vulnerable {
+                player_hit = true;
+            }
+            self.boss = Some(boss);
+        }
+        // hazards like spikes
+        if self.terrain.at(self.player.coords + self.player.wh / 2.).damage && vulnerable {
+            player_hit = true;
+        }
+        // enemy bullets
+        let bullet_count = self.bullets.len();
+        self.bullets.retain(|bullet| bullet.faction != Faction::Enemy || !player_hitbox.contains(bullet.coords));
+        if self.bullets.len() != bullet_count && vulnerable {
+            player_hit = true;
+        }
+        if player_hit {
+            self.hurt_player();
+        }
+        //
+        // D E A T H
+        //
+        if self.player.health <= 0 {
+            self.events.push(WorldEvent::PlayerDied);
+            return;
+        }
+        // collision detection enemies/bullets
+        let mut enemy_index = 0;
+        while enemy_index < self.enemies.len() {
+            let enemy = &self.enemies[enemy_index];
+            let enemy_hitbox = Rect::new(enemy.coords.x, enemy.coords.y, enemy.wh.x, enemy.wh.y);
+            let mut killed = false;
+            let mut bullet_index = 0;
+            while bullet_index < self.bullets.len() {
+                let bullet = &mut self.bullets[bullet_index];
+                if bullet.faction != Faction::Player || bullet.pierce_cooldown > 0 || !enemy_hitbox.contains(bullet.coords) {
+                    bullet_index += 1;
+                    continue;
+                }
+                if bullet.piercing {
+                    bullet.pierce_cooldown = (TILE_SIZE / bullet.velocity.length()).ceil() as i32;
+                    bullet_index += 1;
+                } else {
+                    self.bullets.remove(bullet_index);
+                }
+                if self.enemies[enemy_index].damage(self.player.strength) {
+                    let enemy = self.enemies.remove(enemy_index);
+                    self.on_enemy_killed(&enemy);
+                    // maybe drop an item
+                    if gen_range(0, ITEM_DROP_CHANCE) == 0 {
+                        self.items.push(ItemDrop::new(Item::random(), enemy.coords));
+                    }
+                    killed = true;
+                    break;
+                } else {
+                    self.events.push(WorldEvent::EnemyHit(enemy_index));
+                }
+            }
+            if !killed {
+                enemy_index += 1;
+            }
+        }
+
+
+        // collision detection boss/bullets
+        if let Some(boss) = &mut self.boss {
+            let boss_hitbox = boss.hitbox();
+            let mut bullet_index = 0;
+            let mut killed = false;
+            while bullet_index < self.bullets.len() && !killed {
+                let bullet = &mut self.bullets[bullet_index];
+                if bullet.faction != Faction::Player || bullet.pierce_cooldown > 0 || !boss_hitbox.contains(bullet.coords) {
+                    bullet_index += 1;
+                    continue;
+                }
+                if bullet.piercing {
+                    bullet.pierce_cooldown = (boss.wh.x / bullet.velocity.length()).ceil() as i32;
+                    bullet_index += 1;
+                } else {
+                    self.bullets.remove(bullet_index);
+                }
+                killed = boss.damage(self.player.strength);
+                self.events.push(WorldEvent::BossHit);
+            }
+            if killed {
+                self.boss = None;
+                self.score += BOSS_KILL_BONUS;
+                self.enemies_killcount += 1;
+                self.events.push(WorldEvent::BossDefeated);
+            }
+        }
+
+
+        // items on the map despawn after a while and get picked up on contact
+        let player_hitbox = Rect::new(self.player.coords.x, self.player.coords.y, self.player.wh.x, self.player.wh.y);
+        let mut picked_up = None;
+        self.items.retain_mut(|item| {
+            item.lifetime -= 1;
+            if picked_up.is_none() && item.hitbox().overlaps(&player_hitbox) {
+                picked_up = Some(item.item);
+                return false;
+            }
+            item.lifetime > 0
+        });
+        if let Some(item) = picked_up {
+            self.player.held_effect = Some(item);
+            self.events.push(WorldEvent::ItemPickedUp);
+        }
+
+
+        //
+        // S T A G E   T R A N S I T I O N
+        //
+        if let Some(exit) = self.at_exit().filter(|_| self.exit_open()) {
+            self.stages_cleared += 1;
+            self.score += STAGE_CLEAR_BONUS;
+            if !self.stage_hurt {
+                self.score += NO_HIT_BONUS;
+            }
+            self.stage_hurt = false;
+            // reset game state
+            if let Some(endless) = &mut self.endless {
+                // the next wave in a new room
+                endless.wave += 1;
+                self.stages[0] = endless.stage(&self.enemy_kinds);
+            } else {
+                match self.stage().destination(exit) {
+                    Destination::Stage(next) => self.current_stage = next,
+                    Destination::End => {
+                        self.score += (PAR_TIME - self.run_ticks).max(0) / 60;
+                        self.events.push(WorldEvent::Won);
+                        return;
+                    }
+                }
+            }
+            self.stage_timer = self.stage().timer;
+            self.stage_kills = 0;
+            // nothing of the old room comes along
+            self.enemies.clear();
+            self.bullets.retain(|bullet| bullet.faction != Faction::Enemy);
+            self.items.clear();
+            self.impacts.clear();
+            self.events.push(WorldEvent::StageCleared(exit));
+        }
+    }
+}
@@ -0,0 +1,17 @@
+// the seeded room generator
+use top_down_game::generator::generate;
+
+#[test]
+fn same_seed_same_room() {
+    for seed in [0, 1, 42, u64::MAX] {
+        assert_eq!(generate(seed).to_tiled_json(), generate(seed).to_tiled_json());
+    }
+}
+
+#[test]
+fn other_seed_other_room() {
+    let rooms: Vec<String> = (0..8).map(|seed| generate(seed).to_tiled_json()).collect();
+    for (index, room) in rooms.iter().enumerate() {
+        assert!(!rooms[index + 1..].contains(room), "seed {} makes the same room as a later one", index);
+    }
+}
@@ -0,0 +1,46 @@
+// the high score table
+use top_down_game::highscores::{HighScore, HighScores, MAX_ENTRIES};
+
+fn entry(name: &str, score: i32) -> HighScore {
+    HighScore { name: name.to_string(), score, kills: 0, mode: "campaign".to_string(), stages: 0, won: false }
+}
+
+fn scores(table: &HighScores) -> Vec<i32> {
+    table.entries.iter().map(|entry| entry.score).collect()
+}
+
+#[test]
+fn entries_are_sorted_best_first() {
+    let mut table = HighScores::default();
+    assert_eq!(table.insert(entry("a", 50)), Some(0));
+    assert_eq!(table.insert(entry("b", 80)), Some(0));
+    assert_eq!(table.insert(entry("c", 60)), Some(1));
+    // a tie goes below the older entry
+    assert_eq!(table.insert(entry("d", 60)), Some(2));
+    assert_eq!(scores(&table), [80, 60, 60, 50]);
+    assert_eq!(table.entries[1].name, "c");
+}
+
+#[test]
+fn full_table_drops_the_worst() {
+    let mut table = HighScores::default();
+    for score in 1..=MAX_ENTRIES as i32 {
+        assert!(table.qualifies(score * 10));
+        table.insert(entry("a", score * 10));
+    }
+    assert_eq!(table.entries.len(), MAX_ENTRIES);
+
+    assert!(!table.qualifies(10));
+    assert!(!table.qualifies(5));
+    assert_eq!(table.insert(entry("b", 5)), None);
+    assert!(table.qualifies(15));
+    assert_eq!(table.insert(entry("b", 15)), Some(MAX_ENTRIES - 1));
+    assert_eq!(table.entries.len(), MAX_ENTRIES);
+    assert_eq!(*scores(&table).last().unwrap(), 15);
+}
+
+#[test]
+fn runs_without_points_dont_qualify() {
+    assert!(!HighScores::default().qualifies(0));
+    assert!(HighScores::default().qualifies(1));
+}
@@ -0,0 +1,57 @@
+// binding keys to actions on the controls screen
+use macroquad::prelude::*;
+use nanoserde::{DeJson, SerJson};
+use top_down_game::input::{Action, Keybindings, MAX_BINDINGS};
+
+#[test]
+fn bound_keys_are_added_up_to_the_limit() {
+    let mut keys = Keybindings::default();
+    assert_eq!(keys.keys(Action::MoveUp), [KeyCode::W]);
+    assert_eq!(keys.bind(Action::MoveUp, KeyCode::I), Ok(()));
+    assert_eq!(keys.bind(Action::MoveUp, KeyCode::I), Ok(()));
+    assert_eq!(keys.keys(Action::MoveUp), [KeyCode::W, KeyCode::I]);
+
+    keys.bind(Action::MoveUp, KeyCode::K).unwrap();
+    assert_eq!(keys.keys(Action::MoveUp).len(), MAX_BINDINGS);
+    // the oldest one makes room
+    keys.bind(Action::MoveUp, KeyCode::L).unwrap();
+    assert_eq!(keys.keys(Action::MoveUp), [KeyCode::I, KeyCode::K, KeyCode::L]);
+}
+
+#[test]
+fn a_key_belongs_to_one_action_per_group() {
+    let mut keys = Keybindings::default();
+    assert_eq!(keys.bind(Action::MoveUp, KeyCode::Up), Err(Action::ShootUp));
+    assert_eq!(keys.keys(Action::MoveUp), [KeyCode::W]);
+    // menu actions are a group of their own
+    assert_eq!(keys.bind(Action::MenuUp, KeyCode::W), Ok(()));
+    assert_eq!(keys.bind(Action::Back, KeyCode::W), Err(Action::MenuUp));
+}
+
+#[test]
+fn unbinding_keeps_the_last_key() {
+    let mut keys = Keybindings::default();
+    keys.unbind_last(Action::Confirm);
+    assert_eq!(keys.keys(Action::Confirm), [KeyCode::Enter]);
+    keys.unbind_last(Action::Confirm);
+    assert_eq!(keys.keys(Action::Confirm), [KeyCode::Enter]);
+
+    keys.bind(Action::Confirm, KeyCode::Space).unwrap();
+    keys.reset();
+    assert_eq!(keys.keys(Action::Confirm), Action::Confirm.default_keys());
+}
+
+#[test]
+fn settings_file_names_actions_and_keys() {
+    let keys = Keybindings::deserialize_json(r#"{"move_up": ["Up", "NotAKey"], "pause": [], "use_item": ["Nope"]}"#).unwrap();
+    assert_eq!(keys.keys(Action::MoveUp), [KeyCode::Up]);
+    // missing, empty or unknown mean the default keys
+    assert_eq!(keys.keys(Action::Pause), Action::Pause.default_keys());
+    assert_eq!(keys.keys(Action::UseItem), [KeyCode::Space]);
+    assert_eq!(keys.keys(Action::Back), [KeyCode::Escape]);
+
+    let mut keys = Keybindings::default();
+    keys.bind(Action::UseItem, KeyCode::E).unwrap();
+    let keys = Keybindings::deserialize_json(&keys.serialize_json()).unwrap();
+    assert_eq!(keys.keys(Action::UseItem), [KeyCode::Space, KeyCode::E]);
+}
@@ -0,0 +1,58 @@
+// the flow field on small hand-made rooms
+use macroquad::prelude::*;
+use top_down_game::TILE_SIZE;
+use top_down_game::collision::CollisionType;
+use top_down_game::pathfinding::FlowField;
+
+/// a collision map from rows of `#` (wall) and `.` (floor)
+fn room(rows: &[&str]) -> Vec<CollisionType> {
+    let mut collision_map = vec![];
+    for (y, row) in rows.iter().enumerate() {
+        for (x, tile) in row.chars().enumerate() {
+            collision_map.push(match tile {
+                '#' => CollisionType::Solid(x as u32, y as u32),
+                _ => CollisionType::Empty(x as u32, y as u32),
+            });
+        }
+    }
+    collision_map
+}
+
+/// the centre of tile `x`/`y`
+fn tile(x: u32, y: u32) -> Vec2 {
+    vec2((x as f32 + 0.5) * TILE_SIZE, (y as f32 + 0.5) * TILE_SIZE)
+}
+
+#[test]
+fn distances_go_around_walls() {
+    let mut field = FlowField::from_collision_map(&room(&[
+        "..#..",
+        "..#..",
+        ".....",
+    ]));
+    field.update(tile(4, 0));
+    assert_eq!(field.distance(tile(4, 0)), Some(0));
+    assert_eq!(field.distance(tile(3, 0)), Some(1));
+    // through the gap in the bottom row
+    assert_eq!(field.distance(tile(1, 0)), Some(7));
+    assert_eq!(field.distance(tile(0, 2)), Some(6));
+    assert_eq!(field.distance(tile(2, 0)), None);
+    assert_eq!(field.next_step(tile(1, 0)), Some(tile(1, 1)));
+    assert_eq!(field.next_step(tile(4, 0)), None);
+}
+
+#[test]
+fn enclosed_tiles_are_unreachable() {
+    let mut field = FlowField::from_collision_map(&room(&[
+        "...#.",
+        "...##",
+        ".....",
+    ]));
+    field.update(tile(0, 0));
+    assert!(field.reaches(tile(4, 2)));
+    assert!(!field.reaches(tile(4, 0)));
+    assert_eq!(field.next_step(tile(4, 0)), None);
+    // outside of the room
+    assert!(!field.reaches(vec2(-1., 0.)));
+    assert!(!field.reaches(tile(5, 0)));
+}
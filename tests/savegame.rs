@@ -0,0 +1,50 @@
+// saving a run and continuing it
+use nanoserde::{DeJson, SerJson};
+use top_down_game::enemy::EnemyKinds;
+use top_down_game::items::Item;
+use top_down_game::savegame::{SaveData, SAVE_VERSION};
+use top_down_game::stage::load_levels;
+use top_down_game::world::World;
+
+#[test]
+fn continued_run_is_where_it_was_saved() {
+    let mut world = World::new(load_levels(false), EnemyKinds::load(), 7);
+    world.current_stage = 2;
+    world.stages_cleared = 2;
+    world.player.health = 1;
+    world.player.held_effect = Some(Item::Nuke);
+    world.enemies_killcount = 30;
+    world.score = 420;
+    world.stage_kills = 4;
+    world.stage_hurt = true;
+    world.run_ticks = 5000;
+
+    let json = SaveData::from_world(&world).serialize_json();
+    let save = SaveData::deserialize_json(&json).unwrap();
+    assert_eq!(save.version, SAVE_VERSION);
+    let restored = save.restore(load_levels(false), EnemyKinds::load());
+    assert_eq!(restored.seed, 7);
+    assert_eq!(restored.current_stage, 2);
+    assert_eq!(restored.stages_cleared, 2);
+    assert_eq!(restored.player.health, 1);
+    assert_eq!(restored.player.held_effect, Some(Item::Nuke));
+    assert_eq!(restored.enemies_killcount, 30);
+    assert_eq!(restored.score, 420);
+    assert_eq!(restored.stage_kills, 4);
+    assert!(restored.stage_hurt);
+    assert_eq!(restored.run_ticks, 5000);
+    // back at the start of the stage
+    assert_eq!(restored.stage_timer, restored.stage().timer);
+}
+
+#[test]
+fn saves_of_other_versions_are_ignored() {
+    let world = World::new(load_levels(false), EnemyKinds::load(), 7);
+    let json = SaveData::from_world(&world).serialize_json();
+    assert!(SaveData::from_json(&json).is_some());
+
+    let mut old = SaveData::from_world(&world);
+    old.version = SAVE_VERSION - 1;
+    assert!(SaveData::from_json(&old.serialize_json()).is_none());
+    assert!(SaveData::from_json("{ broken").is_none());
+}
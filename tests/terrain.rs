@@ -0,0 +1,37 @@
+// tile properties stacked from the layers of a map
+use macroquad::prelude::*;
+use nanoserde::DeJson;
+use top_down_game::TILE_SIZE;
+use top_down_game::terrain::Terrain;
+use top_down_game::tiled::TiledMap;
+
+/// one row of three tiles: sand, a crate on sand and a crate on the floor. The crate breaks after two hits
+const MAP: &str = r#"{"width":3, "height":1, "tilesets":[{"firstgid":1, "image":"tilemap.png", "tiles":[
+    {"id":0, "properties":[{"name":"slow", "type":"float", "value":0.5}]},
+    {"id":1, "properties":[{"name":"destructible", "type":"int", "value":2}]}
+]}], "layers":[
+    {"name":"background", "type":"tilelayer", "data":[1, 1, 0]},
+    {"name":"main", "type":"tilelayer", "data":[0, 2, 2]},
+    {"name":"objects", "type":"tilelayer", "data":[0, 0, 0]}
+]}"#;
+
+#[test]
+fn broken_tiles_uncover_the_layers_below() {
+    let mut terrain = Terrain::from_tiled(&TiledMap::deserialize_json(MAP).unwrap());
+    let sand = terrain.tile(0, 0);
+    assert_eq!(sand.slow, 0.5);
+    assert!(terrain.tile(1, 0).blocks_walk);
+
+    let crate_on_sand = vec2(1.5 * TILE_SIZE, 0.5 * TILE_SIZE);
+    assert_eq!(terrain.hit(crate_on_sand), None);
+    assert_eq!(terrain.hit(crate_on_sand), Some((1, 0, "main")));
+    assert_eq!(terrain.tile(1, 0), sand);
+    // nothing left to break
+    assert_eq!(terrain.hit(crate_on_sand), None);
+
+    let crate_on_floor = vec2(2.5 * TILE_SIZE, 0.5 * TILE_SIZE);
+    terrain.hit(crate_on_floor);
+    assert_eq!(terrain.hit(crate_on_floor), Some((2, 0, "main")));
+    assert!(!terrain.tile(2, 0).blocks_walk);
+    assert_eq!(terrain.tile(2, 0).slow, 1.);
+}
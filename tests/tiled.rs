@@ -0,0 +1,39 @@
+// the map checks and the JSON handed to `macroquad_tiled`
+use nanoserde::DeJson;
+use top_down_game::generator;
+use top_down_game::stage::builtin_levels;
+use top_down_game::tiled::{macroquad_tiled_json, TiledMap};
+
+#[test]
+fn property_values_become_strings() {
+    let json = r#"{"layers":[{"name":"entities", "type": "objectgroup", "objects":[{"name":"item", "properties":[{"name":"a","type":"bool","value":true}, {"name":"b", "type":"int", "value": -3}, {"name":"c","type":"string","value":"x, \"y\""}]}]}]}"#;
+    assert_eq!(
+        macroquad_tiled_json(json),
+        r#"{"layers":[{"name":"entities", "type": "objectlayer", "objects":[{"name":"item", "properties":[{"name":"a","type":"bool","value":"true"}, {"name":"b", "type":"int", "value": "-3"}, {"name":"c","type":"string","value":"x, \"y\""}]}]}]}"#,
+    );
+}
+
+#[test]
+fn shipped_and_generated_maps_have_no_problems() {
+    for stage in builtin_levels() {
+        let map = TiledMap::deserialize_json(&stage.map).unwrap();
+        assert!(map.problems().is_empty(), "{}: {:?}", stage.map_path, map.problems());
+    }
+    let map = TiledMap::deserialize_json(&generator::generate(7).to_tiled_json()).unwrap();
+    assert!(map.problems().is_empty(), "{:?}", map.problems());
+}
+
+#[test]
+fn maps_the_game_cant_draw_have_problems() {
+    let map = TiledMap::deserialize_json(r#"{"width":1, "height":1, "tilesets":[{"firstgid":1, "source":"tiles.tsx"}], "layers":[
+        {"name":"background", "type":"tilelayer", "data":[0]},
+        {"name":"main", "type":"tilelayer", "data":[0]},
+        {"name":"main", "type":"imagelayer"}
+    ]}"#).unwrap();
+    assert_eq!(map.problems(), [
+        "missing tile layer `objects`",
+        "tileset without an image (tilesets have to be embedded in the map)",
+        "more than one layer called `main`",
+        "layer `main` has type `imagelayer`, only tile and object layers are supported",
+    ]);
+}
@@ -0,0 +1,207 @@
+// drives the headless `World` through whole ticks, the way the game scene does
+use macroquad::prelude::*;
+use nanoserde::DeJson;
+use top_down_game::boss::{Attack, Boss};
+use top_down_game::bullet::{Bullet, Direction, MAX_BOUNCES};
+use top_down_game::enemy::{Enemy, EnemyKinds};
+use top_down_game::input::InputFrame;
+use top_down_game::items::{Item, ItemDrop, ITEM_LIFETIME};
+use top_down_game::stage::{load_levels, Exit};
+use top_down_game::tiled::TiledMap;
+use top_down_game::world::{World, WorldEvent};
+
+/// a new campaign run on the built-in maps, in its first room
+fn campaign() -> World {
+    let mut world = World::new(load_levels(false), EnemyKinds::load(), 1);
+    enter_stage(&mut world);
+    world
+}
+
+/// set up the room of the current stage, like the game scene does after `StageCleared`
+fn enter_stage(world: &mut World) {
+    let map = TiledMap::deserialize_json(&world.stage().map).unwrap();
+    world.load_stage(&map);
+}
+
+/// step once without any input and hand back what happened
+fn step(world: &mut World) -> Vec<WorldEvent> {
+    world.step(InputFrame::default());
+    world.drain_events()
+}
+
+#[test]
+fn stage_clears_at_the_open_exit() {
+    let mut world = campaign();
+    assert_eq!(world.stage().exits[0].0, Exit::South);
+
+    // the exit stays shut while the stage timer runs
+    world.player.coords.y = 240.;
+    assert!(!step(&mut world).iter().any(|event| matches!(event, WorldEvent::StageCleared(_))));
+    assert_eq!(world.current_stage, 0);
+
+    world.stage_timer = 0;
+    // far from the player, so they can't hurt it before it leaves
+    let ghost = world.enemy_kinds.find("ghost").unwrap();
+    world.enemies.push(Enemy::new(ghost, vec2(128., 32.), &world.enemy_kinds));
+    world.bullets.push(Bullet::hostile(vec2(128., 48.), Vec2::ZERO));
+    let events = step(&mut world);
+    assert!(events.contains(&WorldEvent::StageCleared(Exit::South)));
+    // nothing of the old room comes along
+    assert!(world.enemies.is_empty());
+    assert!(world.bullets.is_empty());
+    assert_eq!(world.current_stage, 1);
+    assert_eq!(world.stages_cleared, 1);
+    assert_eq!(world.stage_timer, world.stages[1].timer);
+    // clear and no-hit bonus
+    assert_eq!(world.score, 150);
+
+    enter_stage(&mut world);
+    assert_eq!(world.player.coords, world.player_start());
+}
+
+#[test]
+fn player_dies_on_the_last_hit() {
+    let mut world = campaign();
+    world.stage_timer = 0;
+    let ghost = world.enemy_kinds.find("ghost").unwrap();
+
+    world.enemies.push(Enemy::new(ghost, world.player.coords, &world.enemy_kinds));
+    let events = step(&mut world);
+    assert!(events.contains(&WorldEvent::PlayerHurt));
+    assert!(!events.contains(&WorldEvent::PlayerDied));
+    assert_eq!(world.player.health, 2);
+    assert!(world.stage_hurt);
+    // getting hurt clears the room
+    assert!(world.enemies.is_empty());
+
+    world.player.health = 1;
+    world.enemies.push(Enemy::new(ghost, world.player.coords, &world.enemy_kinds));
+    let events = step(&mut world);
+    assert!(events.contains(&WorldEvent::PlayerDied));
+    assert_eq!(world.player.health, 0);
+}
+
+#[test]
+fn killed_enemies_are_counted() {
+    let mut world = campaign();
+    world.stage_timer = 0;
+    world.invincibility = true;
+    let ghost = world.enemy_kinds.find("ghost").unwrap();
+    world.enemies.push(Enemy::new(ghost, world.player.coords, &world.enemy_kinds));
+
+    let mut killed = false;
+    for _ in 0..100 {
+        // a bullet right into the enemy every tick. `Bullet::new` takes the shooter's top left corner
+        let Some(enemy) = world.enemies.first() else {
+            break;
+        };
+        let centre = enemy.coords + enemy.wh / 2.;
+        world.bullets.push(Bullet::new(centre - vec2(8., 8.), Direction::Right));
+        if step(&mut world).contains(&WorldEvent::EnemyKilled) {
+            killed = true;
+            break;
+        }
+    }
+    assert!(killed);
+    assert!(world.enemies.is_empty());
+    assert_eq!(world.enemies_killcount, 1);
+    assert_eq!(world.stage_kills, 1);
+    assert_eq!(world.score, world.enemy_kinds.kinds[ghost].score);
+}
+
+#[test]
+fn items_are_picked_up_or_despawn() {
+    let mut world = campaign();
+    world.stage_timer = 0;
+    world.invincibility = true;
+
+    // one under the player, one in a corner far away and one placed by the map
+    world.items.push(ItemDrop::new(Item::Nuke, world.player.coords));
+    world.items.push(ItemDrop::new(Item::Speed, vec2(32., 32.)));
+    world.items.push(ItemDrop::placed(Item::Badge, vec2(208., 32.)));
+    let events = step(&mut world);
+    assert!(events.contains(&WorldEvent::ItemPickedUp));
+    assert_eq!(world.player.held_effect, Some(Item::Nuke));
+    assert_eq!(world.items.len(), 2);
+
+    for _ in 1..ITEM_LIFETIME - 1 {
+        step(&mut world);
+    }
+    assert_eq!(world.items.len(), 2);
+    // dropped items are gone after their lifetime, placed ones stay
+    step(&mut world);
+    assert_eq!(world.items.len(), 1);
+    assert_eq!(world.items[0].item, Item::Badge);
+}
+
+/// a campaign run in the boss room
+fn boss_stage() -> World {
+    let mut world = campaign();
+    world.current_stage = world.stages.iter().position(|stage| stage.boss).unwrap();
+    enter_stage(&mut world);
+    world.invincibility = true;
+    world
+}
+
+#[test]
+fn boss_phases_follow_its_health() {
+    let mut boss = Boss::new();
+    assert_eq!(boss.phase(), 0);
+    boss.hp = boss.max_hp * 2 / 3;
+    assert_eq!(boss.phase(), 1);
+    boss.hp = 1;
+    assert_eq!(boss.phase(), 2);
+    // the last phase lasts until it dies
+    boss.hp = 0;
+    assert_eq!(boss.phase(), 2);
+
+    // in the first phase it only knows how to charge
+    let mut world = boss_stage();
+    for _ in 0..200 {
+        step(&mut world);
+        if world.boss.as_ref().unwrap().attack != Attack::Walk {
+            break;
+        }
+    }
+    assert!(matches!(world.boss.as_ref().unwrap().attack, Attack::Charge { .. }));
+}
+
+#[test]
+fn boss_summons_minions_where_they_fit() {
+    let mut world = boss_stage();
+    let boss = world.boss.as_mut().unwrap();
+    boss.hp = 1;
+    boss.attack = Attack::Summon;
+    step(&mut world);
+
+    // 2 plus the phase at most, leaving out the ones that would end up in a wall
+    assert!(!world.enemies.is_empty());
+    assert!(world.enemies.len() <= 4);
+    assert!(world.enemies.iter().all(|enemy| !enemy.collides(&world.collision_map)));
+    assert_eq!(world.boss.as_ref().unwrap().attack, Attack::Walk);
+}
+
+#[test]
+fn bullets_bounce_off_walls_until_they_run_out_of_bounces() {
+    let world = campaign();
+    // straight up into the wall at the top of the room, away from the door in its middle
+    let start = vec2(40., 40.);
+    assert!(world.terrain.blocks_bullets(vec2(start.x, 8.)));
+
+    // counts the walls a bullet bounced off until one destroys it
+    let bounces = |mut bullet: Bullet| {
+        let mut bounces = 0;
+        for _ in 0..2000 {
+            let velocity = bullet.velocity;
+            if bullet.update(&world.terrain) {
+                return Some(bounces);
+            }
+            if bullet.velocity != velocity {
+                bounces += 1;
+            }
+        }
+        None
+    };
+    assert_eq!(bounces(Bullet::hostile(start, vec2(0., -2.))), Some(0));
+    assert_eq!(bounces(Bullet::hostile(start, vec2(0., -2.)).bouncing(true)), Some(MAX_BOUNCES));
+}